@@ -0,0 +1,226 @@
+//! The "finish" chart: when each member got each star, and how the local scores moved over time.
+//!
+//! This replaces `aoc-finish/finish.html`, which needed a leaderboard dump pasted into it; this
+//! writes a self-contained HTML page of inline SVGs, with no scripts, from a leaderboard.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::leaderboard::{unlock_ts, Leaderboard};
+use crate::scoring;
+
+const WIDTH: f64 = 1000.;
+const MARGIN_LEFT: f64 = 45.;
+const MARGIN_RIGHT: f64 = 210.;
+const MARGIN_TOP: f64 = 10.;
+const MARGIN_BOTTOM: f64 = 40.;
+const ROW_HEIGHT: f64 = 20.;
+const SCORE_HEIGHT: f64 = 400.;
+
+/// Line colors for the score chart, one per member (cycling, if there are more members).
+const PALETTE: &[&str] = &[
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+
+/// Render the chart as an HTML page.
+///
+/// Each day gets a strip showing when members got each star, for the first `window_minutes`
+/// after the puzzle unlocked; members without a star in that window are left off that day.
+pub fn render_html(leaderboard: &Leaderboard, window_minutes: u32) -> anyhow::Result<String> {
+    let year = leaderboard
+        .year()
+        .ok_or_else(|| anyhow::anyhow!("leaderboard's event {:?} isn't a year", leaderboard.event))?;
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(html, "<title>Advent of Code {}</title>", year)?;
+    writeln!(html, "</head>\n<body>")?;
+
+    let last_day = leaderboard
+        .members
+        .values()
+        .filter_map(|m| m.completion_day_level.keys().next_back().copied())
+        .max()
+        .unwrap_or(0);
+    for day in 1..=last_day {
+        writeln!(html, "Dec {}<br>", day)?;
+        html.push_str(&day_svg(leaderboard, year, day, window_minutes));
+        writeln!(html, "<br>")?;
+    }
+
+    writeln!(html, "Local score<br>")?;
+    html.push_str(&score_svg(leaderboard));
+    writeln!(html, "</body>\n</html>")?;
+    Ok(html)
+}
+
+/// A strip for a single day: a row per member, with a silver dot for part 1 and gold for part 2.
+fn day_svg(leaderboard: &Leaderboard, year: i32, day: u8, window_minutes: u32) -> String {
+    let unlock = unlock_ts(year, day);
+    let window = i64::from(window_minutes) * 60;
+    let in_window = |ts: Option<i64>| ts.filter(|&ts| ts - unlock < window);
+
+    let rows = leaderboard
+        .ranked_members()
+        .into_iter()
+        .filter_map(|m| {
+            let silver = in_window(m.star_ts(day, 1));
+            let gold = in_window(m.star_ts(day, 2));
+            if silver.is_none() && gold.is_none() {
+                None
+            } else {
+                Some((m.display_name(), silver, gold))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let height = ROW_HEIGHT * rows.len() as f64;
+    let x_of = |ts: i64| (ts - unlock) as f64 / window as f64 * plot_width;
+
+    let mut svg = String::new();
+    open_svg(&mut svg, height);
+    for (idx, (name, silver, gold)) in rows.iter().enumerate() {
+        let y = ROW_HEIGHT * idx as f64 + 10.;
+        line(&mut svg, 0., y, plot_width, y, "darkgrey");
+        text(&mut svg, plot_width + 3., y + 5., "start", name);
+        for (ts, color) in [(silver, "silver"), (gold, "gold")] {
+            if let Some(ts) = ts {
+                let _ = writeln!(
+                    svg,
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="5" fill="{}"/>"#,
+                    x_of(*ts),
+                    y,
+                    color
+                );
+            }
+        }
+    }
+
+    line(&mut svg, 0., height, plot_width, height, "black");
+    let tick_every = if window_minutes <= 120 { 10 } else { 60 };
+    for minute in (0..=window_minutes).step_by(tick_every) {
+        let x = f64::from(minute) / f64::from(window_minutes) * plot_width;
+        line(&mut svg, x, height, x, height + 5., "black");
+        let label = format!("{}:{:02}", minute / 60, minute % 60);
+        text(&mut svg, x, height + 18., "middle", &label);
+    }
+    close_svg(&mut svg);
+    svg
+}
+
+/// Every member's local score over the whole event.
+fn score_svg(leaderboard: &Leaderboard) -> String {
+    let history = scoring::local_score_history(leaderboard, &scoring::Rules::aoc());
+    let mut svg = String::new();
+    open_svg(&mut svg, SCORE_HEIGHT);
+
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let (first, last) = match (history.first(), history.last()) {
+        (Some(first), Some(last)) => (first.ts, last.ts.max(first.ts + 1)),
+        _ => {
+            close_svg(&mut svg);
+            return svg;
+        }
+    };
+    let max_score = history.iter().map(|p| p.score).max().unwrap_or(0).max(1);
+    let x_of = |ts: i64| (ts - first) as f64 / (last - first) as f64 * plot_width;
+    let y_of = |score: u64| SCORE_HEIGHT - score as f64 / max_score as f64 * SCORE_HEIGHT;
+
+    let mut points = BTreeMap::<u64, Vec<(f64, f64)>>::new();
+    for point in history.iter() {
+        points
+            .entry(point.member)
+            .or_insert_with(|| vec![(x_of(first), y_of(0))])
+            .push((x_of(point.ts), y_of(point.score)));
+    }
+
+    line(&mut svg, 0., SCORE_HEIGHT, plot_width, SCORE_HEIGHT, "black");
+    line(&mut svg, 0., 0., 0., SCORE_HEIGHT, "black");
+    text(&mut svg, -5., 5., "end", &max_score.to_string());
+    text(&mut svg, -5., SCORE_HEIGHT, "end", "0");
+
+    for (idx, member) in leaderboard.ranked_members().into_iter().enumerate() {
+        let color = PALETTE[idx % PALETTE.len()];
+        if let Some(points) = points.get(&member.id) {
+            let coords = points
+                .iter()
+                .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+                coords, color
+            );
+        }
+        let y = 15. + ROW_HEIGHT * idx as f64;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="10" height="10" fill="{}"/>"#,
+            plot_width + 5.,
+            y - 10.,
+            color
+        );
+        let label = format!("{} ({})", member.display_name(), member.local_score);
+        text(&mut svg, plot_width + 20., y, "start", &label);
+    }
+    close_svg(&mut svg);
+    svg
+}
+
+fn open_svg(svg: &mut String, plot_height: f64) {
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif" font-size="12">"#,
+        WIDTH,
+        plot_height + MARGIN_TOP + MARGIN_BOTTOM
+    );
+    let _ = writeln!(svg, r#"<g transform="translate({}, {})">"#, MARGIN_LEFT, MARGIN_TOP);
+}
+
+fn close_svg(svg: &mut String) {
+    svg.push_str("</g>\n</svg>\n");
+}
+
+fn line(svg: &mut String, x1: f64, y1: f64, x2: f64, y2: f64, color: &str) {
+    let _ = writeln!(
+        svg,
+        r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}"/>"#,
+        x1, y1, x2, y2, color
+    );
+}
+
+fn text(svg: &mut String, x: f64, y: f64, anchor: &str, content: &str) {
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" text-anchor="{}">{}</text>"#,
+        x,
+        y,
+        anchor,
+        escape_xml(content)
+    );
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_fixture() {
+        let lb = Leaderboard::from_json(include_str!("../fixtures/leaderboard-2021.json")).unwrap();
+        let html = render_html(&lb, 120).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Dec 1<br>"));
+        assert!(!html.contains("Dec 2<br>"));
+        assert!(html.contains(r#"fill="gold""#));
+        assert!(!html.contains("<script"));
+        assert_eq!(escape_xml("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
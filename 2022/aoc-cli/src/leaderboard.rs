@@ -0,0 +1,243 @@
+//! Private leaderboards: fetching (and caching) them, and showing them in the terminal.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use serde::{Deserialize, Deserializer};
+
+use aoc::term::{Styled, TermColor};
+
+/// AoC asks that the leaderboard API not be hit more than once every 15 minutes.
+pub const CACHE_FOR: Duration = Duration::from_secs(15 * 60);
+
+/// A private leaderboard, as returned by `/leaderboard/private/view/ID.json`.
+#[derive(Debug, Deserialize)]
+pub struct Leaderboard {
+    /// The year.
+    pub event: String,
+    pub members: BTreeMap<u64, Member>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Member {
+    #[serde(deserialize_with = "lenient_int")]
+    pub id: u64,
+    /// `None` for anonymous users.
+    pub name: Option<String>,
+    pub local_score: u64,
+    #[serde(deserialize_with = "lenient_int")]
+    pub last_star_ts: i64,
+    /// The stars this member has, keyed by day.
+    pub completion_day_level: BTreeMap<u8, DayCompletion>,
+}
+
+/// The stars a member has for a single day. (Present only if they have at least the first.)
+#[derive(Debug, Deserialize)]
+pub struct DayCompletion {
+    #[serde(rename = "1")]
+    pub part_1: Star,
+    #[serde(rename = "2", default)]
+    pub part_2: Option<Star>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Star {
+    /// When the star was earned, as a Unix timestamp.
+    #[serde(deserialize_with = "lenient_int")]
+    pub get_star_ts: i64,
+}
+
+/// Older leaderboards (e.g., 2020's) send IDs and timestamps as strings; newer ones as numbers.
+/// Accept either.
+fn lenient_int<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr + TryFrom<u64>,
+    T::Err: fmt::Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumOrStr {
+        Num(u64),
+        Str(String),
+    }
+
+    match NumOrStr::deserialize(deserializer)? {
+        NumOrStr::Num(n) => {
+            T::try_from(n).map_err(|_| serde::de::Error::custom(format!("{} is out of range", n)))
+        }
+        NumOrStr::Str(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+impl Member {
+    /// The member's name, or what AoC shows for anonymous users.
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    /// The timestamp of the given star, if the member has it.
+    pub fn star_ts(&self, day: u8, part: u8) -> Option<i64> {
+        let completion = self.completion_day_level.get(&day)?;
+        match part {
+            1 => Some(completion.part_1.get_star_ts),
+            2 => completion.part_2.as_ref().map(|s| s.get_star_ts),
+            _ => None,
+        }
+    }
+}
+
+impl Leaderboard {
+    /// The year this leaderboard is for, if the event is a year (they always have been).
+    pub fn year(&self) -> Option<i32> {
+        self.event.parse().ok()
+    }
+
+    /// Parse a leaderboard from the JSON the API returns.
+    pub fn from_json(json: &str) -> anyhow::Result<Leaderboard> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load a leaderboard from a saved JSON file.
+    pub fn load(path: &Path) -> anyhow::Result<Leaderboard> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Leaderboard::from_json(&json).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Fetch leaderboard `id` for `year`, unless we fetched it within the last [`CACHE_FOR`].
+    ///
+    /// The session cookie is read from `aoc-cookie` in `year_root`, and the cache is kept in
+    /// `.aoc-cache` there, too.
+    pub fn fetch(year_root: &Path, year: u16, id: u64) -> anyhow::Result<Leaderboard> {
+        let cache_path = cache_path(year_root, year, id);
+        if let Some(json) = read_fresh_cache(&cache_path)? {
+            return Leaderboard::from_json(&json);
+        }
+
+        let cookie = fs::read_to_string(year_root.join("aoc-cookie"))
+            .context("failed to read the session cookie")?;
+        let url = format!("https://adventofcode.com/{}/leaderboard/private/view/{}.json", year, id);
+        let json = aoc_fetch::get(&url, &cookie)?;
+        let leaderboard = Leaderboard::from_json(&json)?;
+
+        fs::create_dir_all(cache_path.parent().unwrap())?;
+        fs::write(&cache_path, &json)
+            .with_context(|| format!("failed to write {}", cache_path.display()))?;
+        Ok(leaderboard)
+    }
+
+    /// The members, best first: by local score, and then by who got their last star earliest.
+    pub fn ranked_members(&self) -> Vec<&Member> {
+        let mut members = self.members.values().collect::<Vec<_>>();
+        members.sort_by_key(|m| (std::cmp::Reverse(m.local_score), m.last_star_ts, m.id));
+        members
+    }
+}
+
+/// When day `day`'s puzzle unlocked, as a Unix timestamp: midnight, US Eastern (UTC−5).
+pub fn unlock_ts(year: i32, day: u8) -> i64 {
+    // Days from 1970-01-01 to December 1st of `year`; see Howard Hinnant's `days_from_civil`.
+    let y = i64::from(year);
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (12 - 3) + 2) / 5;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let dec_1 = era * 146097 + doe - 719468;
+    (dec_1 + i64::from(day) - 1) * 86400 + 5 * 3600
+}
+
+fn cache_path(year_root: &Path, year: u16, id: u64) -> PathBuf {
+    year_root.join(".aoc-cache").join(format!("leaderboard-{}-{}.json", year, id))
+}
+
+fn read_fresh_cache(path: &Path) -> anyhow::Result<Option<String>> {
+    let modified = match fs::metadata(path) {
+        Ok(metadata) => metadata.modified()?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+    if CACHE_FOR <= age {
+        return Ok(None);
+    }
+    Ok(Some(fs::read_to_string(path)?))
+}
+
+/// Print the leaderboard as a ranked table, with a column per day, like the site does. The stars
+/// are colored if stdout is a terminal.
+pub fn print_table<W: Write>(leaderboard: &Leaderboard, out: &mut W) -> io::Result<()> {
+    let last_day = leaderboard
+        .members
+        .values()
+        .filter_map(|m| m.completion_day_level.keys().next_back().copied())
+        .max()
+        .unwrap_or(1);
+
+    let tens = (1..=last_day).map(|d| if d < 10 { ' ' } else { char::from(b'0' + d / 10) });
+    let ones = (1..=last_day).map(|d| char::from(b'0' + d % 10));
+    writeln!(out, "Advent of Code {}", leaderboard.event)?;
+    writeln!(out, "{:11}{}", "", tens.collect::<String>())?;
+    writeln!(out, "{:11}{}", "", ones.collect::<String>())?;
+
+    for (idx, member) in leaderboard.ranked_members().into_iter().enumerate() {
+        write!(out, "{:>3}) {:>5} ", idx + 1, member.local_score)?;
+        for day in 1..=last_day {
+            let color = match (member.star_ts(day, 1), member.star_ts(day, 2)) {
+                (Some(_), Some(_)) => TermColor::BrightYellow,
+                (Some(_), None) => TermColor::White,
+                _ => TermColor::BrightBlack,
+            };
+            write!(out, "{}", Styled::new('*').fg(color))?;
+        }
+        writeln!(out, " {}", member.display_name())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fixtures() {
+        // 2020's leaderboard has string timestamps; 2021's, numeric ones.
+        let lb = Leaderboard::from_json(include_str!("../fixtures/leaderboard-2020.json")).unwrap();
+        assert_eq!(lb.event, "2020");
+        assert_eq!(lb.members.len(), 9);
+        let roy = &lb.members[&995192];
+        assert_eq!(roy.name.as_deref(), Some("Roy Wellington"));
+        assert_eq!(roy.star_ts(1, 1), Some(1606800282));
+        assert_eq!(roy.star_ts(23, 2), None);
+
+        let lb = Leaderboard::from_json(include_str!("../fixtures/leaderboard-2021.json")).unwrap();
+        assert_eq!(lb.event, "2021");
+        assert_eq!(lb.members[&326721].star_ts(1, 2), Some(1638335328));
+    }
+
+    #[test]
+    fn test_unlock_ts() {
+        // 2020-12-01T05:00:00Z and 2021-12-25T05:00:00Z
+        assert_eq!(unlock_ts(2020, 1), 1606798800);
+        assert_eq!(unlock_ts(2021, 25), 1640408400);
+    }
+
+    #[test]
+    fn test_ranked_members() {
+        let lb = Leaderboard::from_json(include_str!("../fixtures/leaderboard-2021.json")).unwrap();
+        let scores = lb.ranked_members().iter().map(|m| m.local_score).collect::<Vec<_>>();
+        let mut sorted = scores.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+        assert_eq!(scores, sorted);
+    }
+}
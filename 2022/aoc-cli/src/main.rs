@@ -0,0 +1,124 @@
+//! `aoc`: the runner for everything that isn't a solution.
+//!
+//! Solution binaries stay small and offline; anything that needs to talk to adventofcode.com
+//! lives here.
+
+use std::path::PathBuf;
+
+use structopt::StructOpt;
+
+mod finish;
+mod leaderboard;
+mod new_day;
+mod scoring;
+mod stats;
+
+#[derive(StructOpt)]
+enum Command {
+    /// Scaffold a new day from the template, register it in the workspace, and fetch its input
+    /// and example.
+    New {
+        year: u16,
+        day: u8,
+        /// Don't fetch the input or the example.
+        #[structopt(long)]
+        offline: bool,
+    },
+    /// Show a private leaderboard, ranked.
+    Leaderboard {
+        year: u16,
+        /// The leaderboard's ID (also the owner's user ID).
+        id: u64,
+        /// Read the leaderboard from this saved JSON file, rather than fetching it.
+        #[structopt(long)]
+        file: Option<PathBuf>,
+    },
+    /// Chart when each member got each star, and the local scores over time, as an HTML page.
+    FinishChart {
+        /// A saved leaderboard JSON file.
+        file: PathBuf,
+        #[structopt(short, long, default_value = "finish.html")]
+        output: PathBuf,
+        /// How long after each puzzle unlocks to chart, in minutes.
+        #[structopt(long, default_value = "120")]
+        window_minutes: u32,
+    },
+    /// Statistics from a saved leaderboard.
+    Stats {
+        /// Which statistics: `times` (star times after unlock, and part 1 → 2), `ranks` (rank
+        /// after each day), or `what-if` (scores under the given rules, against AoC's).
+        #[structopt(possible_values = &["times", "ranks", "what-if"])]
+        report: String,
+        /// A saved leaderboard JSON file.
+        file: PathBuf,
+        /// Write CSV, rather than a table.
+        #[structopt(long)]
+        csv: bool,
+        /// Score this day as worth no points. May be given more than once.
+        #[structopt(long = "ignore-day", number_of_values = 1)]
+        ignore_days: Vec<u8>,
+        /// Score the days that AoC itself awarded no points for (e.g., 2020's day 1).
+        #[structopt(long)]
+        count_zero_point_days: bool,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    match Command::from_args() {
+        Command::New { year, day, offline } => new_day::new_day(year, day, offline),
+        Command::Leaderboard { year, id, file } => {
+            let leaderboard = match file {
+                Some(path) => leaderboard::Leaderboard::load(&path)?,
+                None => leaderboard::Leaderboard::fetch(&find_year_root(year)?, year, id)?,
+            };
+            leaderboard::print_table(&leaderboard, &mut std::io::stdout().lock())?;
+            Ok(())
+        }
+        Command::FinishChart { file, output, window_minutes } => {
+            let leaderboard = leaderboard::Leaderboard::load(&file)?;
+            let html = finish::render_html(&leaderboard, window_minutes)?;
+            std::fs::write(&output, html)?;
+            println!("Wrote {}.", output.display());
+            Ok(())
+        }
+        Command::Stats { report, file, csv, ignore_days, count_zero_point_days } => {
+            let leaderboard = leaderboard::Leaderboard::load(&file)?;
+            let rules = scoring::Rules {
+                ignored_days: ignore_days,
+                count_zero_point_days,
+            };
+            let table = match report.as_str() {
+                "times" => stats::star_times(&leaderboard)?,
+                "ranks" => stats::rank_history(&leaderboard, &rules),
+                "what-if" => stats::what_if(&leaderboard, &rules),
+                _ => unreachable!("structopt checks the possible values"),
+            };
+            let mut stdout = std::io::stdout().lock();
+            if csv {
+                table.write_csv(&mut stdout)?;
+            } else {
+                table.write_text(&mut stdout)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Find the directory for `year`: either the directory we're in (or one of its parents) is named
+/// for the year, or it has a child directory that is.
+fn find_year_root(year: u16) -> anyhow::Result<PathBuf> {
+    let year = year.to_string();
+    let mut dir = std::env::current_dir()?;
+    loop {
+        if dir.file_name().and_then(|n| n.to_str()) == Some(year.as_str()) {
+            return Ok(dir);
+        }
+        let child = dir.join(&year);
+        if child.is_dir() {
+            return Ok(child);
+        }
+        if !dir.pop() {
+            anyhow::bail!("failed to find the directory for {}", year);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Scaffold a new day's crate from `template/`.
+
+use std::fs;
+use anyhow::Context;
+
+const TEMPLATE_CARGO_TOML: &str = include_str!("../template/Cargo.toml");
+const TEMPLATE_MAIN_RS: &str = include_str!("../template/src/main.rs");
+
+pub fn new_day(year: u16, day: u8, offline: bool) -> anyhow::Result<()> {
+    if !(1..=25).contains(&day) {
+        anyhow::bail!("day {} isn't a day of AoC; expected 1 to 25", day);
+    }
+
+    let year_root = crate::find_year_root(year)?;
+    let name = format!("d{}", day);
+    let day_root = year_root.join(&name);
+    if day_root.exists() {
+        anyhow::bail!("{} already exists", day_root.display());
+    }
+
+    // Everything that can fail, short of writing files, goes first, so that a failure doesn't
+    // leave half a day behind.
+    let workspace_toml = year_root.join("Cargo.toml");
+    let manifest = fs::read_to_string(&workspace_toml)
+        .with_context(|| format!("failed to read {}", workspace_toml.display()))?;
+    let manifest = add_workspace_member(&manifest, &name)
+        .with_context(|| format!("failed to register {} in {}", name, workspace_toml.display()))?;
+
+    let (input, example) = if offline {
+        (None, None)
+    } else {
+        let cookie = fs::read_to_string(year_root.join("aoc-cookie"))
+            .context("failed to read the session cookie (or pass --offline)")?;
+        println!("Fetching the input and example for {}, day {}.", year, day);
+        let input = aoc_fetch::get_input(year, day, &cookie)?;
+        let example = aoc_fetch::get_example(year, day, &cookie)?;
+        if example.is_none() {
+            println!("Couldn't find an example on the puzzle's page; test inputs will be empty.");
+        }
+        (Some(input), example)
+    };
+
+    fs::create_dir_all(day_root.join("src"))
+        .with_context(|| format!("failed to create {}", day_root.display()))?;
+    fs::write(
+        day_root.join("Cargo.toml"),
+        TEMPLATE_CARGO_TOML.replace("{{name}}", &name),
+    )?;
+    fs::write(day_root.join("src").join("main.rs"), TEMPLATE_MAIN_RS)?;
+    if let Some(input) = input {
+        fs::write(day_root.join("input"), input)?;
+    }
+    let example = example.unwrap_or_default();
+    fs::write(day_root.join("test-a"), &example)?;
+    fs::write(day_root.join("test-b"), &example)?;
+    println!("Created {}.", day_root.display());
+
+    fs::write(&workspace_toml, manifest)?;
+    println!("Registered {} in the workspace.", name);
+
+    Ok(())
+}
+
+/// Add `name` to the `members` list of the workspace manifest `manifest`, keeping the day crates
+/// in numeric order.
+fn add_workspace_member(manifest: &str, name: &str) -> anyhow::Result<String> {
+    let start = manifest
+        .find("members = [")
+        .ok_or_else(|| anyhow::anyhow!("no `members = [` in the workspace manifest"))?
+        + "members = [".len();
+    let len = manifest[start..]
+        .find(']')
+        .ok_or_else(|| anyhow::anyhow!("unterminated `members` list"))?;
+
+    let mut members = manifest[start..start + len]
+        .split(',')
+        .map(|m| m.trim().trim_matches('"'))
+        .filter(|m| !m.is_empty())
+        .map(|m| m.to_owned())
+        .collect::<Vec<_>>();
+    if members.iter().any(|m| m == name) {
+        anyhow::bail!("{} is already a member", name);
+    }
+    members.push(name.to_owned());
+    members.sort_by(|a, b| member_sort_key(a).cmp(&member_sort_key(b)));
+
+    let mut list = String::from("\n");
+    for member in members {
+        list.push_str(&format!("    \"{}\",\n", member));
+    }
+    Ok(format!("{}{}{}", &manifest[..start], list, &manifest[start + len..]))
+}
+
+/// Non-day crates (`aoc-util`, …) first, then days by number.
+fn member_sort_key(member: &str) -> (Option<u8>, &str) {
+    let day = member.strip_prefix('d').and_then(|n| n.parse().ok());
+    (day, member)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_workspace_member() {
+        let manifest = "[workspace]\nmembers = [\n    \"aoc-util\",\n    \"d1\",\n    \"d10\",\n    \"d9\",\n]\n";
+        let manifest = add_workspace_member(manifest, "d8").unwrap();
+        assert_eq!(
+            manifest,
+            "[workspace]\nmembers = [\n    \"aoc-util\",\n    \"d1\",\n    \"d8\",\n    \"d9\",\n    \"d10\",\n]\n",
+        );
+        assert!(add_workspace_member(&manifest, "d8").is_err());
+    }
+}
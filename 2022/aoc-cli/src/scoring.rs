@@ -0,0 +1,182 @@
+//! Local scores, computed from the star timestamps the same way AoC does.
+//!
+//! For each star (a day's part 1 or part 2), the first member of the leaderboard to get it is
+//! awarded N points, where N is the number of members; the second, N − 1; and so on. Days on
+//! which AoC itself broke don't award points at all; see [`awards_points`].
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use crate::leaderboard::Leaderboard;
+
+/// Days that AoC decided not to award points for, because the site was down at unlock.
+const ZERO_POINT_DAYS: &[(i32, u8)] = &[(2020, 1)];
+
+/// Whether AoC awards points for `day` of `year`.
+pub fn awards_points(year: i32, day: u8) -> bool {
+    !ZERO_POINT_DAYS.contains(&(year, day))
+}
+
+/// Which stars are worth points. [`Rules::aoc`] are AoC's own rules; the rest are for asking
+/// "what if?"
+#[derive(Clone, Debug, Default)]
+pub struct Rules {
+    /// Days that award no points.
+    pub ignored_days: Vec<u8>,
+    /// Award points for days that AoC didn't (see [`awards_points`]).
+    pub count_zero_point_days: bool,
+}
+
+impl Rules {
+    /// AoC's rules.
+    pub fn aoc() -> Rules {
+        Rules::default()
+    }
+
+    /// Whether stars for `day` are worth points under these rules.
+    pub fn counts(&self, year: Option<i32>, day: u8) -> bool {
+        if self.ignored_days.contains(&day) {
+            return false;
+        }
+        self.count_zero_point_days || year.is_none_or(|year| awards_points(year, day))
+    }
+}
+
+/// A member getting a star.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StarEvent {
+    pub member: u64,
+    pub day: u8,
+    pub part: u8,
+    pub ts: i64,
+}
+
+/// A member's local score changing, after they got a star.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScorePoint {
+    pub ts: i64,
+    pub member: u64,
+    pub score: u64,
+}
+
+/// Every star anyone on the leaderboard has gotten, in the order they got them.
+pub fn star_events(leaderboard: &Leaderboard) -> Vec<StarEvent> {
+    let mut events = Vec::new();
+    for member in leaderboard.members.values() {
+        for (&day, completion) in member.completion_day_level.iter() {
+            events.push(StarEvent {
+                member: member.id,
+                day,
+                part: 1,
+                ts: completion.part_1.get_star_ts,
+            });
+            if let Some(star) = &completion.part_2 {
+                events.push(StarEvent {
+                    member: member.id,
+                    day,
+                    part: 2,
+                    ts: star.get_star_ts,
+                });
+            }
+        }
+    }
+    events.sort_by_key(|e| (e.ts, e.member, e.day, e.part));
+    events
+}
+
+/// The points awarded for each star event, in the same order as [`star_events`].
+///
+/// `counts(day, part)` decides whether a star is worth points at all.
+pub fn award_points<F: Fn(u8, u8) -> bool>(
+    leaderboard: &Leaderboard,
+    counts: F,
+) -> Vec<(StarEvent, u64)> {
+    let n_members = u64::try_from(leaderboard.members.len()).unwrap();
+    let mut already_got = BTreeMap::<(u8, u8), u64>::new();
+    star_events(leaderboard)
+        .into_iter()
+        .map(|event| {
+            let before = already_got.entry((event.day, event.part)).or_insert(0);
+            let points = if counts(event.day, event.part) { n_members - *before } else { 0 };
+            *before += 1;
+            (event, points)
+        })
+        .collect()
+}
+
+/// Each member's local score, under `rules`.
+pub fn local_scores(leaderboard: &Leaderboard, rules: &Rules) -> BTreeMap<u64, u64> {
+    let year = leaderboard.year();
+    let mut scores = leaderboard.members.keys().map(|&id| (id, 0)).collect::<BTreeMap<_, _>>();
+    for (event, points) in award_points(leaderboard, |day, _| rules.counts(year, day)) {
+        *scores.get_mut(&event.member).unwrap() += points;
+    }
+    scores
+}
+
+/// How each member's local score grew over time, under `rules`: a point each time a member's
+/// score changed.
+pub fn local_score_history(leaderboard: &Leaderboard, rules: &Rules) -> Vec<ScorePoint> {
+    let year = leaderboard.year();
+    let mut scores = BTreeMap::<u64, u64>::new();
+    award_points(leaderboard, |day, _| rules.counts(year, day))
+        .into_iter()
+        .map(|(event, points)| {
+            let score = scores.entry(event.member).or_insert(0);
+            *score += points;
+            ScorePoint {
+                ts: event.ts,
+                member: event.member,
+                score: *score,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_score_history_matches_aoc() {
+        // 2020's day 1 awarded no points, so this also checks that we skip it.
+        for json in [
+            include_str!("../fixtures/leaderboard-2020.json"),
+            include_str!("../fixtures/leaderboard-2021.json"),
+        ] {
+            let lb = Leaderboard::from_json(json).unwrap();
+            let history = local_score_history(&lb, &Rules::aoc());
+            assert!(history.windows(2).all(|w| w[0].ts <= w[1].ts));
+            for member in lb.members.values() {
+                let last = history.iter().rev().find(|p| p.member == member.id);
+                let score = last.map_or(0, |p| p.score);
+                assert_eq!(score, member.local_score, "{}", member.display_name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_what_if_rules() {
+        let lb = Leaderboard::from_json(include_str!("../fixtures/leaderboard-2020.json")).unwrap();
+        let aoc = local_scores(&lb, &Rules::aoc());
+        for member in lb.members.values() {
+            assert_eq!(aoc[&member.id], member.local_score);
+        }
+
+        // Of the 9 members, 6 got day 1's first star and 5 its second; counting day 1 adds
+        // (9 + 8 + … + 4) + (9 + 8 + … + 5) points.
+        let rules = Rules {
+            count_zero_point_days: true,
+            ..Rules::default()
+        };
+        let with_day_1 = local_scores(&lb, &rules);
+        let total = |scores: &BTreeMap<u64, u64>| scores.values().sum::<u64>();
+        assert_eq!(total(&with_day_1) - total(&aoc), 39 + 35);
+
+        let rules = Rules {
+            ignored_days: (1..=25).collect(),
+            ..Rules::default()
+        };
+        assert_eq!(total(&local_scores(&lb, &rules)), 0);
+    }
+}
@@ -0,0 +1,203 @@
+//! Statistics computed from a leaderboard: how long stars took, how the ranking moved from day to
+//! day, and what the scores would have been under other rules.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::leaderboard::{unlock_ts, Leaderboard};
+use crate::scoring::{self, Rules};
+
+/// A table of results, which can be written either aligned for the terminal, or as CSV.
+pub struct Table {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn new(header: &[&str]) -> Table {
+        Table {
+            header: header.iter().map(|h| (*h).to_owned()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Write the table with its columns padded to line up.
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let mut widths = self.header.iter().map(|h| h.chars().count()).collect::<Vec<_>>();
+        for row in self.rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        for row in std::iter::once(&self.header).chain(self.rows.iter()) {
+            let cells = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect::<Vec<_>>();
+            writeln!(out, "{}", cells.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+
+    /// Write the table as CSV.
+    pub fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for row in std::iter::once(&self.header).chain(self.rows.iter()) {
+            let cells = row.iter().map(|c| csv_escape(c)).collect::<Vec<_>>();
+            writeln!(out, "{}", cells.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_owned()
+    }
+}
+
+/// Format a duration in seconds as `H:MM:SS`.
+fn format_duration(secs: i64) -> String {
+    let sign = if secs < 0 { "-" } else { "" };
+    let secs = secs.abs();
+    format!("{}{}:{:02}:{:02}", sign, secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// For each member and day: when they got each star, relative to the puzzle unlocking, and the
+/// time between part 1 and part 2.
+pub fn star_times(leaderboard: &Leaderboard) -> anyhow::Result<Table> {
+    let year = leaderboard
+        .year()
+        .ok_or_else(|| anyhow::anyhow!("leaderboard's event {:?} isn't a year", leaderboard.event))?;
+    let mut table = Table::new(&["member", "day", "part 1", "part 2", "part 1 → 2"]);
+    for member in leaderboard.ranked_members() {
+        for &day in member.completion_day_level.keys() {
+            let unlock = unlock_ts(year, day);
+            let part_1 = member.star_ts(day, 1);
+            let part_2 = member.star_ts(day, 2);
+            let after_unlock = |ts: Option<i64>| ts.map(|ts| format_duration(ts - unlock));
+            let delta = part_1.zip(part_2).map(|(a, b)| format_duration(b - a));
+            table.rows.push(vec![
+                member.display_name(),
+                day.to_string(),
+                after_unlock(part_1).unwrap_or_default(),
+                after_unlock(part_2).unwrap_or_default(),
+                delta.unwrap_or_default(),
+            ]);
+        }
+    }
+    Ok(table)
+}
+
+/// Each member's rank, under `rules`, counting only the points from the days so far.
+///
+/// Members with the same score share a rank.
+pub fn rank_history(leaderboard: &Leaderboard, rules: &Rules) -> Table {
+    let year = leaderboard.year();
+    let awarded = scoring::award_points(leaderboard, |day, _| rules.counts(year, day));
+    let last_day = awarded.iter().map(|(e, _)| e.day).max().unwrap_or(0);
+
+    let mut header = vec!["member".to_owned()];
+    header.extend((1..=last_day).map(|d| format!("day {}", d)));
+    let mut ranks = leaderboard
+        .members
+        .keys()
+        .map(|&id| (id, Vec::new()))
+        .collect::<BTreeMap<_, _>>();
+
+    for day in 1..=last_day {
+        let mut scores = leaderboard.members.keys().map(|&id| (id, 0)).collect::<BTreeMap<_, _>>();
+        for (event, points) in awarded.iter().filter(|(e, _)| e.day <= day) {
+            *scores.get_mut(&event.member).unwrap() += points;
+        }
+        for (&id, &score) in scores.iter() {
+            let rank = 1 + scores.values().filter(|&&other| score < other).count();
+            ranks.get_mut(&id).unwrap().push(rank);
+        }
+    }
+
+    let rows = leaderboard
+        .ranked_members()
+        .into_iter()
+        .map(|member| {
+            let mut row = vec![member.display_name()];
+            row.extend(ranks[&member.id].iter().map(|r| r.to_string()));
+            row
+        })
+        .collect();
+    Table { header, rows }
+}
+
+/// Each member's local score under AoC's rules, beside their score under `rules`.
+pub fn what_if(leaderboard: &Leaderboard, rules: &Rules) -> Table {
+    let aoc = scoring::local_scores(leaderboard, &Rules::aoc());
+    let what_if = scoring::local_scores(leaderboard, rules);
+    let mut rows = leaderboard
+        .members
+        .values()
+        .map(|m| (what_if[&m.id], aoc[&m.id], m.display_name()))
+        .collect::<Vec<_>>();
+    rows.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+
+    let mut table = Table::new(&["rank", "member", "score", "AoC score", "difference"]);
+    for (idx, (what_if, aoc, name)) in rows.into_iter().enumerate() {
+        let difference = i128::from(what_if) - i128::from(aoc);
+        table.rows.push(vec![
+            (idx + 1).to_string(),
+            name,
+            what_if.to_string(),
+            aoc.to_string(),
+            format!("{:+}", difference),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Leaderboard {
+        Leaderboard::from_json(include_str!("../fixtures/leaderboard-2020.json")).unwrap()
+    }
+
+    #[test]
+    fn test_star_times() {
+        let table = star_times(&fixture()).unwrap();
+        // Roy got day 1's stars at 1606800282 and 1606800421; it unlocked at 1606798800.
+        let row = table.rows.iter().find(|r| r[0] == "Roy Wellington" && r[1] == "1").unwrap();
+        assert_eq!(row[2..], ["0:24:42", "0:27:01", "0:02:19"]);
+        let row = table.rows.iter().find(|r| r[0] == "Roy Wellington" && r[1] == "23").unwrap();
+        assert_eq!(row[3..], ["", ""]);
+    }
+
+    #[test]
+    fn test_rank_history() {
+        let lb = fixture();
+        let table = rank_history(&lb, &Rules::aoc());
+        assert_eq!(table.header.len(), 1 + 24);
+        // After the last day, the ranking is the leaderboard's.
+        let last = table.rows.iter().map(|r| r.last().unwrap().as_str()).collect::<Vec<_>>();
+        assert_eq!(last[..3], ["1", "2", "3"]);
+        // Nobody had any points after day 1, which was worth nothing.
+        assert!(table.rows.iter().all(|r| r[1] == "1"));
+    }
+
+    #[test]
+    fn test_what_if_and_csv() {
+        let rules = Rules {
+            count_zero_point_days: true,
+            ..Rules::default()
+        };
+        let table = what_if(&fixture(), &rules);
+        assert_eq!(table.rows.len(), 9);
+
+        let mut csv = Vec::new();
+        table.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("rank,member,score,AoC score,difference\n1,"));
+        assert_eq!(csv_escape("a, \"b\""), "\"a, \"\"b\"\"\"");
+    }
+}
@@ -0,0 +1,23 @@
+#[allow(unused_imports)]
+use aoc::prelude::*;
+
+type Input = Vec<String>;
+
+fn parser(input_file: &aoc::InputFile<'_>) -> anyhow::Result<Input> {
+    input_file.one_item_per_line(|line| Ok(line.to_owned()))
+}
+
+fn part_a(input: &Input) -> i64 {
+    todo!()
+}
+
+fn part_b(input: &Input) -> i64 {
+    todo!()
+}
+
+aoc::aoc!(parser, part_a, part_b, None, None);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+}
@@ -0,0 +1,102 @@
+//! Talking to adventofcode.com: fetching puzzle inputs, examples and anything else behind the
+//! session cookie.
+//!
+//! This is its own crate so that only what asks for it links an HTTP client: the `aoc` CLI
+//! depends on it directly, and solutions only get it through aoc-util's opt-in `autofetch`
+//! feature. (Were it a feature of aoc-util that the CLI turned on, Cargo would turn it on for
+//! every day built alongside the CLI in the workspace, too.)
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+/// Fetch the input for `year`/`day` into `day_root/input`, unless that file already exists.
+///
+/// The session cookie is read from `aoc-cookie`, in the parent of `day_root`.
+pub fn fetch_input(day_root: &Path, year: u16, day: u8) -> anyhow::Result<()> {
+    let input_path = day_root.join("input");
+    if input_path.exists() {
+        println!("Auto-fetch: the input file already exists, skipping fetch.");
+        return Ok(());
+    }
+
+    println!("Auto-fetch: fetching the input file for {}, day {}.", year, day);
+
+    let cookie = get_cookie(day_root)?;
+    let input_text = get_input(year, day, &cookie)?;
+
+    File::create(input_path)?.write_all(input_text.as_bytes())?;
+    Ok(())
+}
+
+/// Fetch the input for `year`/`day`.
+pub fn get_input(year: u16, day: u8, cookie: &str) -> anyhow::Result<String> {
+    let url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
+    get(&url, cookie)
+}
+
+/// Fetch the first example from the puzzle's page, for `year`/`day`.
+///
+/// AoC doesn't mark which block is "the example", but it's nearly always the first
+/// `<pre><code>` block on the page, so that is what this returns. `None` if the page has no such
+/// block.
+pub fn get_example(year: u16, day: u8, cookie: &str) -> anyhow::Result<Option<String>> {
+    let url = format!("https://adventofcode.com/{}/day/{}", year, day);
+    let page = get(&url, cookie)?;
+    Ok(extract_example(&page))
+}
+
+fn extract_example(page: &str) -> Option<String> {
+    const START: &str = "<pre><code>";
+    const END: &str = "</code></pre>";
+    let start = page.find(START)? + START.len();
+    let len = page[start..].find(END)?;
+    Some(unescape_html(&page[start..start + len]))
+}
+
+/// Strip tags (e.g., `<em>`) and undo the handful of entities AoC uses in examples.
+fn unescape_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for ch in html.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if in_tag => (),
+            ch => text.push(ch),
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Perform an authenticated GET against the AoC site, returning the body.
+pub fn get(url: &str, cookie: &str) -> anyhow::Result<String> {
+    let response = ureq::get(url)
+        .set("Cookie", cookie.trim())
+        .call()
+        .map_err(|err| anyhow::anyhow!("GET {} failed: {}", url, err))?;
+    Ok(response.into_string()?)
+}
+
+/// Read the session cookie, which lives in `aoc-cookie` in the parent of `day_root`.
+pub fn get_cookie<P: AsRef<Path>>(day_root: P) -> anyhow::Result<String> {
+    let mut p = day_root.as_ref().join("..");
+    p.push("aoc-cookie");
+    Ok(fs::read_to_string(p)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_example() {
+        let page = "<p>For example:</p>\n<pre><code>$ cd /\n<em>14848514</em> b.txt\n&lt;-&gt; &amp;\n</code></pre>\n<pre><code>second</code></pre>";
+        assert_eq!(extract_example(page).unwrap(), "$ cd /\n14848514 b.txt\n<-> &\n");
+        assert_eq!(extract_example("<p>no examples</p>"), None);
+    }
+}
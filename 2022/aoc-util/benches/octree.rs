@@ -0,0 +1,258 @@
+//! Octree against the other ways of running 2021 day 22's reboot steps: the point set part A
+//! used, day 22's own `TreeCube`, and `cuboid::Region`. Then point queries against the result,
+//! which is where the tree does best.
+//!
+//!     cargo bench -p aoc-util --bench octree
+
+use std::collections::HashSet;
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use aoc_util::cuboid::{Cuboid, Region};
+use aoc_util::octree::Octree;
+use aoc_util::point::Point;
+
+/// Reboot steps, like the puzzle's: `(on, box)`, from a fixed pseudo-random sequence.
+fn steps(count: usize, reach: i64, seed: u64) -> Vec<(bool, Cuboid<3>)> {
+    let mut state = seed;
+    let mut next = |n: i64| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) as i64 % n
+    };
+    (0..count)
+        .map(|_| {
+            let mut lo = [0; 3];
+            let mut hi = [0; 3];
+            for axis in 0..3 {
+                lo[axis] = next(2 * reach) - reach;
+                hi[axis] = lo[axis] + next(reach);
+            }
+            (next(4) != 0, Cuboid::inclusive(Point(lo), Point(hi)))
+        })
+        .collect()
+}
+
+fn time<T, F: FnMut() -> T>(name: &str, runs: u32, mut f: F) -> T {
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..runs {
+        let start = Instant::now();
+        result = Some(f());
+        best = best.min(start.elapsed());
+    }
+    println!("{:>24}: {:?}", name, best);
+    result.unwrap()
+}
+
+fn main() {
+    let small = steps(20, 50, 1);
+    let bounds = Cuboid::inclusive(Point([-50; 3]), Point([100; 3]));
+    println!("{} steps within -50..=100:", small.len());
+    let by_points = time("HashSet of points", 3, || {
+        let mut on = HashSet::new();
+        for (flip, cuboid) in small.iter() {
+            let [x, y, z] = cuboid.axes;
+            for p in (x.start..x.end)
+                .flat_map(|x| (y.start..y.end).map(move |y| (x, y)))
+                .flat_map(|(x, y)| (z.start..z.end).map(move |z| Point([x, y, z])))
+            {
+                if *flip {
+                    on.insert(p);
+                } else {
+                    on.remove(&p);
+                }
+            }
+        }
+        on.len() as u64
+    });
+    let by_tree = time("Octree", 10, || reboot_octree(bounds, &small));
+    let by_tree_cube = time("TreeCube", 10, || reboot_tree_cube(bounds, &small));
+    let by_region = time("Region", 10, || reboot_region(&small));
+    assert_eq!((by_points, by_points, by_points), (by_tree, by_tree_cube, by_region));
+
+    let big = steps(100, 200, 2);
+    let bounds = Cuboid::inclusive(Point([-200; 3]), Point([400; 3]));
+    println!("{} steps within -200..=400:", big.len());
+    let by_tree = time("Octree", 3, || reboot_octree(bounds, &big));
+    let by_tree_cube = time("TreeCube", 3, || reboot_tree_cube(bounds, &big));
+    let by_region = time("Region", 3, || reboot_region(&big));
+    assert_eq!((by_tree, by_tree), (by_tree_cube, by_region));
+
+    let mut tree = Octree::new(bounds, false);
+    let mut region = Region::new();
+    for (on, cuboid) in big.iter() {
+        tree.fill(cuboid, *on);
+        if *on {
+            region.insert(*cuboid);
+        } else {
+            region.remove(*cuboid);
+        }
+    }
+    let probes = steps(10_000, 200, 3).into_iter().map(|(_, c)| Point(c.axes.map(|i| i.start)));
+    let probes = probes.collect::<Vec<_>>();
+    println!(
+        "{} point queries, against {} tree nodes or {} cuboids:",
+        probes.len(),
+        tree.node_count(),
+        region.cuboids().len()
+    );
+    let by_tree = time("Octree::get", 3, || {
+        probes.iter().filter(|p| tree.get(black_box(p)) == Some(&true)).count()
+    });
+    let by_region = time("Region::contains", 3, || {
+        probes.iter().filter(|p| region.contains(black_box(p))).count()
+    });
+    assert_eq!(by_tree, by_region);
+}
+
+fn reboot_octree(bounds: Cuboid<3>, steps: &[(bool, Cuboid<3>)]) -> u64 {
+    let mut reactor = Octree::new(bounds, false);
+    for (on, cuboid) in steps {
+        reactor.fill(cuboid, *on);
+    }
+    reactor.volume()
+}
+
+fn reboot_region(steps: &[(bool, Cuboid<3>)]) -> u64 {
+    let mut reactor = Region::new();
+    for (on, cuboid) in steps {
+        if *on {
+            reactor.insert(*cuboid);
+        } else {
+            reactor.remove(*cuboid);
+        }
+    }
+    reactor.volume()
+}
+
+fn reboot_tree_cube(bounds: Cuboid<3>, steps: &[(bool, Cuboid<3>)]) -> u64 {
+    let mut reactor = TreeCube::new(CubeSpec::from(bounds), TCubeState::Off);
+    for (on, cuboid) in steps {
+        reactor.fill(&CubeSpec::from(*cuboid), *on);
+    }
+    reactor.volume()
+}
+
+/// 2021 day 22's cube, with inclusive `(lo, hi)` ranges on each axis.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct CubeSpec {
+    x: (i64, i64),
+    y: (i64, i64),
+    z: (i64, i64),
+}
+
+impl CubeSpec {
+    fn is_empty(&self) -> bool {
+        self.x.1 < self.x.0 || self.y.1 < self.y.0 || self.z.1 < self.z.0
+    }
+
+    fn volume(&self) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+        let len = |(lo, hi): (i64, i64)| (hi - lo + 1) as u64;
+        len(self.x) * len(self.y) * len(self.z)
+    }
+
+    fn contains(&self, other: &CubeSpec) -> bool {
+        let within = |a: (i64, i64), b: (i64, i64)| a.0 <= b.0 && b.1 <= a.1;
+        within(self.x, other.x) && within(self.y, other.y) && within(self.z, other.z)
+    }
+
+    fn intersects(&self, other: &CubeSpec) -> bool {
+        let overlap = |a: (i64, i64), b: (i64, i64)| a.0 <= b.1 && b.0 <= a.1;
+        overlap(self.x, other.x) && overlap(self.y, other.y) && overlap(self.z, other.z)
+    }
+
+    /// Day 22's split, in the same order. Day 22 starts the upper half at the midpoint, rounded
+    /// down, so a range two wide splits into an empty half and itself, and the fill never bottoms
+    /// out; this starts it one past. Halves of a range one wide are still empty, and left empty.
+    fn split(&self) -> [CubeSpec; 8] {
+        let halves = |(lo, hi): (i64, i64)| {
+            let mid = (lo + hi).div_euclid(2) + 1;
+            [(lo, mid - 1), (mid, hi)]
+        };
+        let (xs, ys, zs) = (halves(self.x), halves(self.y), halves(self.z));
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|i| CubeSpec {
+            x: xs[i >> 2],
+            y: ys[(i >> 1) & 1],
+            z: zs[i & 1],
+        })
+    }
+}
+
+impl From<Cuboid<3>> for CubeSpec {
+    fn from(cuboid: Cuboid<3>) -> CubeSpec {
+        let [x, y, z] = cuboid.axes;
+        CubeSpec {
+            x: (x.start, x.end - 1),
+            y: (y.start, y.end - 1),
+            z: (z.start, z.end - 1),
+        }
+    }
+}
+
+/// Day 22's octree, which it declared but never filled: each node is all on, all off, or split
+/// eight ways, with the children behind `Arc`s as day 22 had them. (Nothing shares them here, so
+/// `Arc::make_mut` never has to copy.)
+#[derive(Clone)]
+struct TreeCube {
+    state: TCubeState,
+    cube: CubeSpec,
+}
+
+#[derive(Clone)]
+enum TCubeState {
+    On,
+    Off,
+    Mixed([Arc<TreeCube>; 8]),
+}
+
+impl TreeCube {
+    fn new(cube: CubeSpec, state: TCubeState) -> TreeCube {
+        TreeCube { state, cube }
+    }
+
+    fn fill(&mut self, target: &CubeSpec, on: bool) {
+        if self.cube.is_empty() || !self.cube.intersects(target) {
+            return;
+        }
+        let uniform = if on { TCubeState::On } else { TCubeState::Off };
+        if target.contains(&self.cube) {
+            self.state = uniform;
+            return;
+        }
+        match self.state {
+            TCubeState::On if on => return,
+            TCubeState::Off if !on => return,
+            TCubeState::Mixed(_) => {}
+            ref state => {
+                let split = self.cube.split().map(|c| Arc::new(TreeCube::new(c, state.clone())));
+                self.state = TCubeState::Mixed(split);
+            }
+        }
+        if let TCubeState::Mixed(children) = &mut self.state {
+            for child in children.iter_mut() {
+                Arc::make_mut(child).fill(target, on);
+            }
+            // Merge the children back up, if the fill left them all the same.
+            let settled = |c: &Arc<TreeCube>| match c.state {
+                TCubeState::On => on,
+                TCubeState::Off => !on,
+                TCubeState::Mixed(_) => false,
+            };
+            if children.iter().all(|c| c.cube.is_empty() || settled(c)) {
+                self.state = uniform;
+            }
+        }
+    }
+
+    fn volume(&self) -> u64 {
+        match &self.state {
+            TCubeState::On => self.cube.volume(),
+            TCubeState::Off => 0,
+            TCubeState::Mixed(children) => children.iter().map(|c| c.volume()).sum(),
+        }
+    }
+}
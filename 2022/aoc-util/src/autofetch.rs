@@ -1,35 +1,17 @@
 //! Auto-fetch the puzzle input.
+//!
+//! Only compiled with the `autofetch` feature; without it, solution binaries never touch the
+//! network.
 
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-pub async fn autofetch() -> anyhow::Result<()> {
-    let (day, root) = determine_day_root()?;
-    let input_path = root.join("input");
-    if input_path.exists() {
-        println!("Auto-fetch: the input file already exists, skipping fetch.");
-        return Ok(());
-    }
-
-    println!("Auto-fetch: fetching today's input file.");
-
-    let cookie = get_cookie(root)?;
-
-    let url = format!("https://adventofcode.com/2022/day/{}/input", day);
+/// The year of AoC that this crate is for.
+pub const YEAR: u16 = 2022;
 
-    let client = reqwest::Client::new();
-    let input_text = client
-        .get(url)
-        .header(reqwest::header::COOKIE, cookie)
-        .send()
-        .await?
-        .error_for_status()?
-        .text()
-        .await?;
-
-    File::create(input_path)?.write_all(input_text.as_bytes())?;
-    Ok(())
+/// Fetch the input for the day whose crate we're currently running in, unless it already exists.
+pub fn autofetch() -> anyhow::Result<()> {
+    let (day, root) = determine_day_root()?;
+    aoc_fetch::fetch_input(&root, YEAR, day)
 }
 
 fn determine_day_root() -> anyhow::Result<(u8, PathBuf)> {
@@ -50,14 +32,8 @@ fn determine_day_root() -> anyhow::Result<(u8, PathBuf)> {
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("could not turn directory name into string"))?;
     let n = dir_name
-        .strip_prefix("d")
+        .strip_prefix('d')
         .ok_or_else(|| anyhow::anyhow!("directory name did not start with 'd'"))?;
     let n = n.parse()?;
     Ok((n, dir))
 }
-
-fn get_cookie<P: AsRef<Path>>(root: P) -> anyhow::Result<String> {
-    let mut p = root.as_ref().join("..");
-    p.push("aoc-cookie");
-    Ok(fs::read_to_string(p)?)
-}
@@ -0,0 +1,538 @@
+//! Cellular automata: step a [`Map`], a [`SparseMap`] or a set of active cells with a rule, until
+//! it settles down or starts repeating.
+//!
+//! Each automaton keeps two grids, and each step writes the next generation into the spare one
+//! before swapping them, so a rule always sees the whole of the previous generation.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+pub use crate::cycle::Cycle;
+use crate::map::{AsCoord, BoundCoord, FreeCoord, Map};
+use crate::sparse::SparseMap;
+
+/// Which cells count as a cell's neighbors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Adjacency {
+    /// The four cells up, down, left and right.
+    Cardinal,
+    /// The eight cells around, diagonals included.
+    Diagonals,
+}
+
+/// Offsets of the neighbors, in reading order: top row, then the middle row, then the bottom row.
+const CARDINAL_OFFSETS: &[(i64, i64)] = &[(0, -1), (-1, 0), (1, 0), (0, 1)];
+const DIAGONAL_OFFSETS: &[(i64, i64)] =
+    &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+impl Adjacency {
+    fn offsets(self) -> &'static [(i64, i64)] {
+        match self {
+            Adjacency::Cardinal => CARDINAL_OFFSETS,
+            Adjacency::Diagonals => DIAGONAL_OFFSETS,
+        }
+    }
+}
+
+/// Something that steps from one generation to the next.
+pub trait Automaton {
+    /// A snapshot of the current generation, to spot repeats with.
+    type State: Eq + Hash;
+
+    /// Advance one generation. Returns whether anything changed.
+    fn step(&mut self) -> bool;
+
+    fn state(&self) -> Self::State;
+
+    /// Advance `n` generations.
+    fn run(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Step until a step changes nothing. Returns the number of that step, counting from 1.
+    ///
+    /// (This is the number 2021 day 25 asks for. It doesn't return if the automaton never
+    /// settles; use [`Automaton::find_cycle`] for those.)
+    fn run_until_stable(&mut self) -> usize {
+        let mut steps = 0usize;
+        loop {
+            steps = steps.checked_add(1).unwrap();
+            if !self.step() {
+                return steps;
+            }
+        }
+    }
+
+    /// Step until a generation repeats one seen before, and report the cycle.
+    ///
+    /// Afterwards, the automaton is `start + period` steps along, in the same state as it was
+    /// after `start` steps.
+    fn find_cycle(&mut self) -> Cycle {
+        let mut seen = HashMap::new();
+        let mut steps = 0usize;
+        loop {
+            if let Some(start) = seen.insert(self.state(), steps) {
+                return Cycle {
+                    start,
+                    period: steps - start,
+                };
+            }
+            self.step();
+            steps = steps.checked_add(1).unwrap();
+        }
+    }
+}
+
+/// A cell of a [`Map`], as seen by a [`MapAutomaton`]'s rule.
+pub struct MapCell<'a, T> {
+    pub coord: BoundCoord,
+    pub value: &'a T,
+    map: &'a Map<T>,
+    adjacency: Adjacency,
+}
+
+impl<'a, T> MapCell<'a, T> {
+    /// The whole previous generation, for rules that look further than the neighbors.
+    pub fn map(&self) -> &'a Map<T> {
+        self.map
+    }
+
+    /// The neighbors that are on the map, in reading order.
+    pub fn neighbors(&self) -> impl Iterator<Item = &'a T> + '_ {
+        let origin = self.coord.unbind();
+        self.adjacency
+            .offsets()
+            .iter()
+            .filter_map(move |&(dx, dy)| origin.offset(dx, dy).bind(self.map))
+            .map(move |c| self.map.at(&c).unwrap())
+    }
+
+    /// The number of neighbors for which `pred` holds.
+    pub fn count<P: Fn(&T) -> bool>(&self, pred: P) -> usize {
+        self.neighbors().filter(|t| pred(t)).count()
+    }
+}
+
+/// An automaton over a [`Map`]. Cells off the edge of the map don't exist, and aren't neighbors.
+pub struct MapAutomaton<T, R> {
+    current: Map<T>,
+    next: Map<T>,
+    adjacency: Adjacency,
+    rule: R,
+}
+
+impl<T, R> MapAutomaton<T, R>
+where
+    T: Clone + PartialEq,
+    R: FnMut(MapCell<'_, T>) -> T,
+{
+    /// Start from `map`; `rule` gives each cell's value in the next generation.
+    pub fn new(map: Map<T>, adjacency: Adjacency, rule: R) -> MapAutomaton<T, R> {
+        MapAutomaton {
+            next: map.clone(),
+            current: map,
+            adjacency,
+            rule,
+        }
+    }
+
+    /// The current generation.
+    pub fn map(&self) -> &Map<T> {
+        &self.current
+    }
+
+    pub fn into_map(self) -> Map<T> {
+        self.current
+    }
+}
+
+impl<T, R> Automaton for MapAutomaton<T, R>
+where
+    T: Clone + Eq + Hash,
+    R: FnMut(MapCell<'_, T>) -> T,
+{
+    type State = Map<T>;
+
+    fn step(&mut self) -> bool {
+        let mut changed = false;
+        for (coord, value) in self.current.tiles() {
+            let position = coord.as_coord();
+            let cell = MapCell {
+                coord,
+                value,
+                map: &self.current,
+                adjacency: self.adjacency,
+            };
+            let new = (self.rule)(cell);
+            changed |= new != *value;
+            *self.next.at_mut(position).unwrap() = new;
+        }
+        std::mem::swap(&mut self.current, &mut self.next);
+        changed
+    }
+
+    fn state(&self) -> Map<T> {
+        self.current.clone()
+    }
+}
+
+/// A cell of a [`SparseMap`], as seen by a [`SparseAutomaton`]'s rule.
+pub struct SparseCell<'a, T> {
+    pub coord: FreeCoord,
+    pub value: &'a T,
+    map: &'a SparseMap<T>,
+    adjacency: Adjacency,
+}
+
+impl<'a, T: Clone + PartialEq> SparseCell<'a, T> {
+    /// The whole previous generation, for rules that look further than the neighbors.
+    pub fn map(&self) -> &'a SparseMap<T> {
+        self.map
+    }
+
+    /// The neighbors, in reading order.
+    pub fn neighbors(&self) -> impl Iterator<Item = &'a T> + '_ {
+        let coord = self.coord;
+        self.adjacency.offsets().iter().map(move |&(dx, dy)| self.map.get(coord.offset(dx, dy)))
+    }
+
+    /// The number of neighbors for which `pred` holds.
+    pub fn count<P: Fn(&T) -> bool>(&self, pred: P) -> usize {
+        self.neighbors().filter(|t| pred(t)).count()
+    }
+}
+
+/// An automaton over an unbounded [`SparseMap`].
+///
+/// The rule is applied to every cell within one of the map's bounds, and once to a cell out in
+/// the background, to find the next generation's background: so a rule can light up the whole
+/// infinite plane, as 2021 day 20's does.
+pub struct SparseAutomaton<T, R> {
+    current: SparseMap<T>,
+    next: SparseMap<T>,
+    adjacency: Adjacency,
+    rule: R,
+}
+
+impl<T, R> SparseAutomaton<T, R>
+where
+    T: Clone + PartialEq,
+    R: FnMut(SparseCell<'_, T>) -> T,
+{
+    /// Start from `map`; `rule` gives each cell's value in the next generation.
+    pub fn new(map: SparseMap<T>, adjacency: Adjacency, rule: R) -> SparseAutomaton<T, R> {
+        SparseAutomaton {
+            next: SparseMap::new(map.background().clone()),
+            current: map,
+            adjacency,
+            rule,
+        }
+    }
+
+    /// The current generation.
+    pub fn map(&self) -> &SparseMap<T> {
+        &self.current
+    }
+
+    pub fn into_map(self) -> SparseMap<T> {
+        self.current
+    }
+}
+
+impl<T, R> Automaton for SparseAutomaton<T, R>
+where
+    T: Clone + Eq + Hash,
+    R: FnMut(SparseCell<'_, T>) -> T,
+{
+    type State = (T, Vec<(FreeCoord, T)>);
+
+    fn step(&mut self) -> bool {
+        let bounds = self.current.bounds();
+
+        // Far enough out that it and all its neighbors are background:
+        let outside = match bounds {
+            Some(b) => b.max.offset(2, 2),
+            None => FreeCoord { x: 0, y: 0 },
+        };
+        let background = self.current.background();
+        let cell = SparseCell {
+            coord: outside,
+            value: background,
+            map: &self.current,
+            adjacency: self.adjacency,
+        };
+        let new_background = (self.rule)(cell);
+        let mut changed = new_background != *background;
+        self.next.reset(new_background);
+
+        if let Some(bounds) = bounds {
+            for y in bounds.min.y - 1..=bounds.max.y + 1 {
+                for x in bounds.min.x - 1..=bounds.max.x + 1 {
+                    let coord = FreeCoord { x, y };
+                    let value = self.current.get(coord);
+                    let cell = SparseCell {
+                        coord,
+                        value,
+                        map: &self.current,
+                        adjacency: self.adjacency,
+                    };
+                    let new = (self.rule)(cell);
+                    changed |= new != *value;
+                    self.next.set(coord, new);
+                }
+            }
+        }
+        std::mem::swap(&mut self.current, &mut self.next);
+        changed
+    }
+
+    /// The background, and the other cells in reading order.
+    fn state(&self) -> (T, Vec<(FreeCoord, T)>) {
+        let mut cells = self.current.iter().map(|(c, v)| (c, v.clone())).collect::<Vec<_>>();
+        cells.sort_by_key(|(c, _)| (c.y, c.x));
+        (self.current.background().clone(), cells)
+    }
+}
+
+/// A Conway-style automaton over a set of active cells, for any kind of coordinate: a cell's next
+/// state depends only on whether it's active, and how many of its neighbors are.
+///
+/// This suits 2020 day 17's [`Point`](crate::point::Point)s, where the space has more dimensions
+/// than a map.
+pub struct SetAutomaton<C, N, R> {
+    active: HashSet<C>,
+    next: HashSet<C>,
+    counts: HashMap<C, usize>,
+    neighbors: N,
+    rule: R,
+}
+
+impl<C, N, I, R> SetAutomaton<C, N, R>
+where
+    C: Clone + Eq + Hash,
+    N: Fn(&C) -> I,
+    I: IntoIterator<Item = C>,
+    R: FnMut(bool, usize) -> bool,
+{
+    /// Start with `active` active; `neighbors` lists a cell's neighbors, and `rule` says whether
+    /// a cell is active in the next generation, from whether it is now and its count of active
+    /// neighbors.
+    ///
+    /// Only cells with at least one active neighbor are asked about, so a rule that activates a
+    /// cell with none would need the whole (infinite) space.
+    pub fn new<A>(active: A, neighbors: N, rule: R) -> SetAutomaton<C, N, R>
+    where
+        A: IntoIterator<Item = C>,
+    {
+        SetAutomaton {
+            active: active.into_iter().collect(),
+            next: HashSet::new(),
+            counts: HashMap::new(),
+            neighbors,
+            rule,
+        }
+    }
+
+    /// The currently active cells.
+    pub fn active(&self) -> &HashSet<C> {
+        &self.active
+    }
+
+    pub fn into_active(self) -> HashSet<C> {
+        self.active
+    }
+}
+
+impl<C, N, I, R> Automaton for SetAutomaton<C, N, R>
+where
+    C: Clone + Ord + Hash,
+    N: Fn(&C) -> I,
+    I: IntoIterator<Item = C>,
+    R: FnMut(bool, usize) -> bool,
+{
+    type State = Vec<C>;
+
+    fn step(&mut self) -> bool {
+        self.counts.clear();
+        for cell in self.active.iter() {
+            for neighbor in (self.neighbors)(cell) {
+                *self.counts.entry(neighbor).or_insert(0) += 1;
+            }
+        }
+
+        self.next.clear();
+        let mut changed = false;
+        for cell in self.active.iter() {
+            let count = self.counts.get(cell).copied().unwrap_or(0);
+            if (self.rule)(true, count) {
+                self.next.insert(cell.clone());
+            } else {
+                changed = true;
+            }
+        }
+        for (cell, &count) in self.counts.iter() {
+            if !self.active.contains(cell) && (self.rule)(false, count) {
+                self.next.insert(cell.clone());
+                changed = true;
+            }
+        }
+        std::mem::swap(&mut self.active, &mut self.next);
+        changed
+    }
+
+    /// The active cells, sorted.
+    fn state(&self) -> Vec<C> {
+        let mut cells = self.active.iter().cloned().collect::<Vec<_>>();
+        cells.sort();
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::Point;
+
+    fn parse_map(rows: &[&str]) -> Map<char> {
+        Map::from_rows(rows.iter().map(|r| r.chars().collect()).collect())
+    }
+
+    const SEATS: &[&str] = &[
+        "L.LL.LL.LL",
+        "LLLLLLL.LL",
+        "L.L.L..L..",
+        "LLLL.LL.LL",
+        "L.LL.LL.LL",
+        "L.LLLLL.LL",
+        "..L.L.....",
+        "LLLLLLLLLL",
+        "L.LLLLLL.L",
+        "L.LLLLL.LL",
+    ];
+
+    fn occupied(map: &Map<char>) -> usize {
+        map.tiles().filter(|(_, t)| **t == '#').count()
+    }
+
+    #[test]
+    fn test_seating_adjacent() {
+        // 2020 day 11, part 1.
+        let mut seats = MapAutomaton::new(parse_map(SEATS), Adjacency::Diagonals, |cell| {
+            match (*cell.value, cell.count(|t| *t == '#')) {
+                ('L', 0) => '#',
+                ('#', n) if 4 <= n => 'L',
+                (t, _) => t,
+            }
+        });
+        assert_eq!(seats.run_until_stable(), 6);
+        assert_eq!(occupied(seats.map()), 37);
+    }
+
+    #[test]
+    fn test_seating_line_of_sight() {
+        // 2020 day 11, part 2: the first seat seen in each direction is a neighbor.
+        let mut seats = MapAutomaton::new(parse_map(SEATS), Adjacency::Diagonals, |cell| {
+            let visible = DIAGONAL_OFFSETS
+                .iter()
+                .filter(|&&(dx, dy)| {
+                    let mut c = cell.coord.unbind();
+                    loop {
+                        c = c.offset(dx, dy);
+                        match c.bind(cell.map()).map(|b| *cell.map().at(&b).unwrap()) {
+                            Some('.') => continue,
+                            Some('#') => return true,
+                            _ => return false,
+                        }
+                    }
+                })
+                .count();
+            match (*cell.value, visible) {
+                ('L', 0) => '#',
+                ('#', n) if 5 <= n => 'L',
+                (t, _) => t,
+            }
+        });
+        seats.run_until_stable();
+        assert_eq!(occupied(seats.map()), 26);
+    }
+
+    #[test]
+    fn test_sea_cucumbers() {
+        // 2021 day 25: the east-facing herd moves, then the south-facing one, wrapping around.
+        let map = parse_map(&[
+            "v...>>.vv>",
+            ".vv>>.vv..",
+            ">>.>v>...v",
+            ">>v>>.>.v.",
+            "v>v.vv.v..",
+            ">.>>..v...",
+            ".vv..>.>v.",
+            "v.v..>>v.v",
+            "....v..v.>",
+        ]);
+        let mut herd = MapAutomaton::new(map, Adjacency::Cardinal, |cell| {
+            let map = cell.map();
+            let (w, h) = (map.width(), map.height());
+            let at = |x: usize, y: usize| *map.at((x % w, y % h)).unwrap();
+            let east = |x: usize, y: usize| match at(x, y) {
+                '>' if at(x + 1, y) == '.' => '.',
+                '.' if at(x + w - 1, y) == '>' => '>',
+                t => t,
+            };
+            let (x, y) = (cell.coord.x, cell.coord.y);
+            match east(x, y) {
+                'v' if east(x, y + 1) == '.' => '.',
+                '.' if east(x, y + h - 1) == 'v' => 'v',
+                t => t,
+            }
+        });
+        assert_eq!(herd.run_until_stable(), 58);
+    }
+
+    #[test]
+    fn test_blinker_period() {
+        let life = |cell: SparseCell<'_, bool>| {
+            matches!((*cell.value, cell.count(|b| *b)), (true, 2) | (_, 3))
+        };
+        let blinker = [(0, 1), (1, 1), (2, 1)].iter().map(|&(x, y)| FreeCoord { x, y }).collect();
+        let mut life = SparseAutomaton::new(blinker, Adjacency::Diagonals, life);
+        assert_eq!(life.find_cycle(), Cycle { start: 0, period: 2 });
+        assert_eq!(life.map().to_string_single_char(|b| if *b { '#' } else { '.' }), "###\n");
+
+        // A block is a still life: a fixed point.
+        let mut block = MapAutomaton::new(parse_map(&["##", "##"]), Adjacency::Diagonals, |c| {
+            match (*c.value, c.count(|t| *t == '#')) {
+                ('#', 2) | ('#', 3) | (_, 3) => '#',
+                _ => '.',
+            }
+        });
+        assert_eq!(block.find_cycle(), Cycle { start: 0, period: 1 });
+    }
+
+    #[test]
+    fn test_flipping_background() {
+        // Every cell toggles, including the infinite background.
+        let start = [FreeCoord { x: 0, y: 0 }].iter().copied().collect::<SparseMap<bool>>();
+        let mut image = SparseAutomaton::new(start, Adjacency::Cardinal, |cell| !*cell.value);
+        image.step();
+        assert!(*image.map().background());
+        assert!(!*image.map().get(FreeCoord { x: 0, y: 0 }));
+        assert_eq!(image.map().len(), 1);
+        assert_eq!(image.find_cycle(), Cycle { start: 0, period: 2 });
+    }
+
+    #[test]
+    fn test_conway_cubes() {
+        // 2020 day 17, part 1.
+        let start = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter().map(|&(x, y)| Point([x, y, 0]));
+        let mut cubes = SetAutomaton::new(
+            start,
+            |p: &Point<3>| p.neighbors(),
+            |active, n| n == 3 || (active && n == 2),
+        );
+        cubes.run(6);
+        assert_eq!(cubes.active().len(), 112);
+    }
+}
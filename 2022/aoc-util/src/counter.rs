@@ -0,0 +1,262 @@
+//! A multiset: how many of each thing there are, like Python's `collections.Counter`.
+//!
+//! (Counting one more of something is `insert`, not `add`: `counter.add(x)` would find
+//! [`Add::add`], which adds two whole counters.)
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::ops::{Add, AddAssign, Index};
+
+/// Counts of things. Only things with a count above zero are kept.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Counter<T: Eq + Hash> {
+    counts: HashMap<T, usize>,
+}
+
+impl<T: Eq + Hash> Counter<T> {
+    pub fn new() -> Counter<T> {
+        Counter {
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Count one more `item`.
+    pub fn insert(&mut self, item: T) {
+        self.insert_n(item, 1);
+    }
+
+    /// Count `n` more of `item`.
+    pub fn insert_n(&mut self, item: T, n: usize) {
+        if n != 0 {
+            let count = self.counts.entry(item).or_insert(0);
+            *count = count.checked_add(n).unwrap();
+        }
+    }
+
+    /// Count `n` fewer of `item`, stopping at zero. Returns how many were taken away.
+    pub fn remove_n(&mut self, item: &T, n: usize) -> usize {
+        let count = match self.counts.get_mut(item) {
+            Some(count) => count,
+            None => return 0,
+        };
+        if n < *count {
+            *count -= n;
+            n
+        } else {
+            self.counts.remove(item).unwrap()
+        }
+    }
+
+    /// How many of `item` there are; zero if it's never been counted.
+    pub fn get(&self, item: &T) -> usize {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    /// The number of different things counted.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The sum of all the counts.
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Each thing, and its count, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.counts.iter().map(|(item, &count)| (item, count))
+    }
+
+    pub fn into_map(self) -> HashMap<T, usize> {
+        self.counts
+    }
+}
+
+impl<T: Eq + Hash + Clone> Counter<T> {
+    /// Add all of `other`'s counts to this one's.
+    pub fn merge(&mut self, other: &Counter<T>) {
+        for (item, count) in other.iter() {
+            self.insert_n(item.clone(), count);
+        }
+    }
+
+    /// Take `other`'s counts away from this one's, stopping each at zero.
+    pub fn subtract(&mut self, other: &Counter<T>) {
+        for (item, count) in other.iter() {
+            self.remove_n(item, count);
+        }
+    }
+}
+
+/// Orderings by count. Ties are broken by the things themselves, smallest first, so that the
+/// results don't depend on the hash map's order.
+impl<T: Eq + Hash + Ord> Counter<T> {
+    /// Everything, most common first.
+    pub fn sorted_by_count(&self) -> Vec<(&T, usize)> {
+        let mut sorted = self.iter().collect::<Vec<_>>();
+        sorted.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        sorted
+    }
+
+    /// The `n` most common things, most common first.
+    pub fn most_common(&self, n: usize) -> Vec<(&T, usize)> {
+        let mut sorted = self.sorted_by_count();
+        sorted.truncate(n);
+        sorted
+    }
+
+    /// The thing with the highest count, or `None` if there's nothing.
+    pub fn max(&self) -> Option<(&T, usize)> {
+        self.iter().min_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)))
+    }
+
+    /// The thing with the lowest count, or `None` if there's nothing.
+    pub fn min(&self) -> Option<(&T, usize)> {
+        self.iter().min_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)))
+    }
+}
+
+impl<T: Eq + Hash> Default for Counter<T> {
+    fn default() -> Counter<T> {
+        Counter::new()
+    }
+}
+
+impl<T: Eq + Hash> FromIterator<T> for Counter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Counter<T> {
+        let mut counter = Counter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<T: Eq + Hash> Extend<T> for Counter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+/// Counts given in bulk, as `(item, count)`.
+impl<T: Eq + Hash> Extend<(T, usize)> for Counter<T> {
+    fn extend<I: IntoIterator<Item = (T, usize)>>(&mut self, iter: I) {
+        for (item, count) in iter {
+            self.insert_n(item, count);
+        }
+    }
+}
+
+impl<T: Eq + Hash> Add for Counter<T> {
+    type Output = Counter<T>;
+
+    fn add(mut self, rhs: Counter<T>) -> Counter<T> {
+        self += rhs;
+        self
+    }
+}
+
+impl<T: Eq + Hash> AddAssign for Counter<T> {
+    fn add_assign(&mut self, rhs: Counter<T>) {
+        self.extend(rhs.counts);
+    }
+}
+
+/// `counter[&item]` is the same as `counter.get(&item)`: zero for things never counted.
+impl<T: Eq + Hash> Index<&T> for Counter<T> {
+    type Output = usize;
+
+    fn index(&self, item: &T) -> &usize {
+        self.counts.get(item).unwrap_or(&0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let mut counter = "mississippi".chars().collect::<Counter<_>>();
+        assert_eq!((counter.len(), counter.total()), (4, 11));
+        assert_eq!((counter[&'s'], counter[&'z'], counter.get(&'m')), (4, 0, 1));
+        assert_eq!(counter.most_common(2), [(&'i', 4), (&'s', 4)]);
+        assert_eq!(counter.max(), Some((&'i', 4)));
+        assert_eq!(counter.min(), Some((&'m', 1)));
+        let sorted = counter.sorted_by_count().into_iter().map(|(c, _)| *c).collect::<String>();
+        assert_eq!(sorted, "ispm");
+
+        assert_eq!(counter.remove_n(&'p', 5), 2);
+        assert_eq!(counter.remove_n(&'s', 1), 1);
+        assert_eq!((counter.len(), counter.total()), (3, 8));
+
+        let other = "miss".chars().collect::<Counter<_>>();
+        counter.subtract(&other);
+        assert_eq!(counter.sorted_by_count(), [(&'i', 3), (&'s', 1)]);
+        counter.merge(&other);
+        assert_eq!(counter.clone() + other, "iiiiisssssmm".chars().collect());
+        assert_eq!(Counter::<char>::new().max(), None);
+    }
+
+    #[test]
+    fn test_lanternfish() {
+        // 2021 day 6: fish counted by their timers, rather than one by one.
+        let mut fish = [3u8, 4, 3, 1, 2].iter().copied().collect::<Counter<_>>();
+        let mut totals = Vec::new();
+        for day in 1..=256 {
+            let mut next = Counter::new();
+            for (&timer, count) in fish.iter() {
+                if timer == 0 {
+                    next.extend(vec![(6, count), (8, count)]);
+                } else {
+                    next.insert_n(timer - 1, count);
+                }
+            }
+            fish = next;
+            if [18, 80, 256].contains(&day) {
+                totals.push(fish.total());
+            }
+        }
+        assert_eq!(totals, [26, 5934, 26984457539]);
+    }
+
+    #[test]
+    fn test_polymer() {
+        // 2021 day 14: the polymer counted by pairs of elements.
+        let rules = [
+            "CH B", "HH N", "CB H", "NH C", "HB C", "HC B", "HN C", "NN C", "BH H", "NC B", "NB B",
+            "BN B", "BB N", "BC B", "CC N", "CN C",
+        ]
+        .iter()
+        .map(|r| {
+            let r = r.as_bytes();
+            ((r[0], r[1]), r[3])
+        })
+        .collect::<HashMap<_, _>>();
+        let template = b"NNCB";
+        let mut pairs = template.windows(2).map(|w| (w[0], w[1])).collect::<Counter<_>>();
+        let mut spreads = Vec::new();
+        for step in 1..=40 {
+            let mut next = Counter::new();
+            for (&(a, b), count) in pairs.iter() {
+                let c = rules[&(a, b)];
+                next.insert_n((a, c), count);
+                next.insert_n((c, b), count);
+            }
+            pairs = next;
+            if step == 10 || step == 40 {
+                // Every element is the first of a pair, except the last, which never changes.
+                let mut elements = Counter::<u8>::new();
+                elements.extend(pairs.iter().map(|(&(a, _), count)| (a, count)));
+                elements.insert(*template.last().unwrap());
+                spreads.push(elements.max().unwrap().1 - elements.min().unwrap().1);
+            }
+        }
+        assert_eq!(spreads, [1588, 2188189693529]);
+    }
+}
@@ -0,0 +1,189 @@
+//! Axis-aligned boxes in `N` dimensions, and regions made of them: rectangles for `N = 2`, and
+//! 2021 day 22's reactor cuboids for `N = 3`.
+
+use std::convert::TryFrom;
+
+use crate::interval::Interval;
+use crate::point::{BoundingBox, Point};
+
+/// An axis-aligned box: a half-open [`Interval`] along each axis.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Cuboid<const N: usize> {
+    pub axes: [Interval<i64>; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(axes: [Interval<i64>; N]) -> Cuboid<N> {
+        Cuboid { axes }
+    }
+
+    /// The box with corners `min` and `max`, both included.
+    pub fn inclusive(min: Point<N>, max: Point<N>) -> Cuboid<N> {
+        let mut axes = [Interval::new(0, 0); N];
+        for (axis, interval) in axes.iter_mut().enumerate() {
+            *interval = Interval::inclusive(min[axis], max[axis]);
+        }
+        Cuboid { axes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(|i| i.is_empty())
+    }
+
+    /// The number of points in the box.
+    pub fn volume(&self) -> u64 {
+        self.axes.iter().map(|i| u64::try_from(i.len()).unwrap()).product()
+    }
+
+    pub fn contains(&self, point: &Point<N>) -> bool {
+        self.axes.iter().zip(point.0.iter()).all(|(i, v)| i.contains(*v))
+    }
+
+    /// Whether every point in `other` is in this box.
+    pub fn contains_cuboid(&self, other: &Cuboid<N>) -> bool {
+        let mut axes = self.axes.iter().zip(other.axes.iter());
+        other.is_empty() || axes.all(|(a, b)| a.contains_interval(b))
+    }
+
+    /// The points in both boxes, or `None` if there aren't any.
+    pub fn intersection(&self, other: &Cuboid<N>) -> Option<Cuboid<N>> {
+        let mut axes = self.axes;
+        for (axis, interval) in axes.iter_mut().enumerate() {
+            *interval = interval.intersection(&other.axes[axis])?;
+        }
+        Some(Cuboid { axes })
+    }
+
+    /// The points in this box but not in `other`, as at most `2N` disjoint boxes.
+    ///
+    /// Axis by axis, the slabs of this box either side of `other` are cut off, and what's left
+    /// carries on to the next axis; what's left at the end is the intersection, and is dropped.
+    pub fn subtract(&self, other: &Cuboid<N>) -> Vec<Cuboid<N>> {
+        let cut = match self.intersection(other) {
+            None => return vec![*self],
+            Some(cut) => cut,
+        };
+        let mut pieces = Vec::new();
+        let mut rest = *self;
+        for axis in 0..N {
+            for slab in rest.axes[axis].subtract(&cut.axes[axis]) {
+                let mut piece = rest;
+                piece.axes[axis] = slab;
+                pieces.push(piece);
+            }
+            rest.axes[axis] = cut.axes[axis];
+        }
+        pieces
+    }
+}
+
+impl<const N: usize> From<BoundingBox<N>> for Cuboid<N> {
+    fn from(bounds: BoundingBox<N>) -> Cuboid<N> {
+        Cuboid::inclusive(bounds.min, bounds.max)
+    }
+}
+
+/// A region of space, as a set of disjoint cuboids.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Region<const N: usize> {
+    cuboids: Vec<Cuboid<N>>,
+}
+
+impl<const N: usize> Region<N> {
+    pub fn new() -> Region<N> {
+        Region {
+            cuboids: Vec::new(),
+        }
+    }
+
+    /// Add every point in `cuboid` to the region.
+    pub fn insert(&mut self, cuboid: Cuboid<N>) {
+        if cuboid.is_empty() {
+            return;
+        }
+        self.remove(cuboid);
+        self.cuboids.push(cuboid);
+    }
+
+    /// Remove every point in `cuboid` from the region.
+    pub fn remove(&mut self, cuboid: Cuboid<N>) {
+        self.cuboids = self.cuboids.iter().flat_map(|c| c.subtract(&cuboid)).collect();
+    }
+
+    pub fn contains(&self, point: &Point<N>) -> bool {
+        self.cuboids.iter().any(|c| c.contains(point))
+    }
+
+    /// The number of points in the region.
+    pub fn volume(&self) -> u64 {
+        self.cuboids.iter().map(|c| c.volume()).sum()
+    }
+
+    /// The region, restricted to the points in `cuboid`.
+    pub fn clip(&self, cuboid: &Cuboid<N>) -> Region<N> {
+        Region {
+            cuboids: self.cuboids.iter().filter_map(|c| c.intersection(cuboid)).collect(),
+        }
+    }
+
+    /// The disjoint cuboids making up the region, in no particular order.
+    pub fn cuboids(&self) -> &[Cuboid<N>] {
+        &self.cuboids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube(lo: [i64; 3], hi: [i64; 3]) -> Cuboid<3> {
+        Cuboid::inclusive(Point(lo), Point(hi))
+    }
+
+    #[test]
+    fn test_subtract() {
+        let a = cube([0, 0, 0], [9, 9, 9]);
+        let b = cube([3, -5, 3], [5, 20, 5]);
+        let pieces = a.subtract(&b);
+        assert_eq!(pieces.len(), 4);
+        assert_eq!(pieces.iter().map(|c| c.volume()).sum::<u64>(), 1000 - 3 * 10 * 3);
+        for (i, p) in pieces.iter().enumerate() {
+            assert!(a.contains_cuboid(p));
+            assert!(p.intersection(&b).is_none());
+            assert!(pieces[i + 1..].iter().all(|q| p.intersection(q).is_none()));
+        }
+        assert_eq!(a.subtract(&a), []);
+        assert_eq!(a.subtract(&cube([20, 0, 0], [30, 9, 9])), [a]);
+
+        let rect = Cuboid::inclusive(Point([0, 0]), Point([3, 3]));
+        assert_eq!(rect.subtract(&Cuboid::inclusive(Point([1, 1]), Point([2, 2]))).len(), 4);
+    }
+
+    #[test]
+    fn test_reactor_reboot() {
+        // 2021 day 22's first example.
+        let steps = [
+            (true, cube([10, 10, 10], [12, 12, 12])),
+            (true, cube([11, 11, 11], [13, 13, 13])),
+            (false, cube([9, 9, 9], [11, 11, 11])),
+            (true, cube([10, 10, 10], [10, 10, 10])),
+        ];
+        let mut reactor = Region::new();
+        let mut volumes = Vec::new();
+        for (on, c) in steps.iter() {
+            if *on {
+                reactor.insert(*c);
+            } else {
+                reactor.remove(*c);
+            }
+            volumes.push(reactor.volume());
+        }
+        assert_eq!(volumes, [27, 27 + 19, 27 + 19 - 8, 39]);
+        assert!(reactor.contains(&Point([10, 10, 10])));
+        assert!(!reactor.contains(&Point([11, 11, 11])));
+        assert_eq!(reactor.clip(&cube([12, 12, 12], [50, 50, 50])).volume(), 8);
+
+        let bounds = BoundingBox { min: Point([9, 9, 9]), max: Point([13, 13, 13]) };
+        assert_eq!(Cuboid::from(bounds).volume(), bounds.volume());
+    }
+}
@@ -0,0 +1,167 @@
+//! Spotting when a sequence of states starts repeating, so "what does it look like after a
+//! trillion steps?" only takes as many steps as it takes to come back around.
+//!
+//! These use Brent's algorithm, which only compares states, and keeps two of them at a time; the
+//! states don't need to be hashable, and nothing grows with the length of the run. The price is
+//! stepping up to about three times as often as remembering every state would.
+
+/// Where a run ended up repeating itself.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Cycle {
+    /// The number of steps before the first state that repeats.
+    pub start: usize,
+    /// How many steps it takes to come back around; 1 for a fixed point.
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The first step that's in the same state as step `n`.
+    pub fn earliest_equivalent(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+}
+
+/// Find the cycle in `initial`, `step(initial)`, `step(step(initial))`, …
+///
+/// Doesn't return if the states never repeat.
+pub fn find_cycle<S: Clone + Eq, F: FnMut(&S) -> S>(initial: S, mut step: F) -> Cycle {
+    match brent(&initial, &mut step, usize::MAX) {
+        Ok(cycle) => cycle,
+        Err(_) => panic!("no cycle in {} steps", usize::MAX),
+    }
+}
+
+/// The state after `n` steps from `initial`. If it starts repeating before then, this skips the
+/// laps of the cycle, rather than stepping through them all.
+pub fn nth_state<S: Clone + Eq, F: FnMut(&S) -> S>(initial: S, mut step: F, n: usize) -> S {
+    if n == 0 {
+        return initial;
+    }
+    match brent(&initial, &mut step, n) {
+        Err(state) => state,
+        Ok(cycle) => {
+            let mut state = initial;
+            for _ in 0..cycle.earliest_equivalent(n) {
+                state = step(&state);
+            }
+            state
+        }
+    }
+}
+
+/// Brent's algorithm. Gives up with `Err` of the state after `limit` steps, if the period hasn't
+/// been found by then.
+fn brent<S: Clone + Eq, F: FnMut(&S) -> S>(
+    initial: &S,
+    step: &mut F,
+    limit: usize,
+) -> Result<Cycle, S> {
+    // Find the period: the hare runs ahead, and the tortoise jumps to it at each power of two,
+    // until the hare laps back onto it.
+    let mut power = 1usize;
+    let mut period = 1;
+    let mut steps = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while tortoise != hare {
+        if steps == limit {
+            return Err(hare);
+        }
+        if power == period {
+            tortoise = hare.clone();
+            power = power.checked_mul(2).unwrap();
+            period = 0;
+        }
+        hare = step(&hare);
+        steps += 1;
+        period += 1;
+    }
+
+    // Find the start: with the hare a period ahead, they meet at the first repeated state.
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Ok(Cycle { start, period })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    use quickcheck::quickcheck;
+
+    fn naive_cycle(initial: u64, step: impl Fn(&u64) -> u64) -> Cycle {
+        let mut seen = HashMap::new();
+        let mut state = initial;
+        for steps in 0.. {
+            if let Some(start) = seen.insert(state, steps) {
+                return Cycle {
+                    start,
+                    period: steps - start,
+                };
+            }
+            state = step(&state);
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let square_plus_one = |x: &u64| (x * x + 1) % 255;
+        assert_eq!(find_cycle(3, square_plus_one), naive_cycle(3, square_plus_one));
+        assert_eq!(find_cycle(7, |x| *x), Cycle { start: 0, period: 1 });
+        assert_eq!(find_cycle(0, |x| (x + 1).min(5)), Cycle { start: 5, period: 1 });
+        assert_eq!(find_cycle(0, |x| (x + 1) % 9), Cycle { start: 0, period: 9 });
+
+        // States that can't be hashed: a deck, cutting the top card to the bottom.
+        #[derive(Clone, PartialEq, Eq)]
+        struct Deck(Vec<u8>);
+        let cut = |d: &Deck| {
+            let mut cards = d.0.clone();
+            cards.rotate_left(1);
+            Deck(cards)
+        };
+        assert_eq!(find_cycle(Deck(vec![1, 2, 3, 4, 5]), cut), Cycle { start: 0, period: 5 });
+    }
+
+    #[test]
+    fn test_nth_state() {
+        let step = |x: &u64| (x * x + 1) % 1000;
+        let mut state = 3;
+        let mut states = vec![state];
+        for _ in 0..2000 {
+            state = step(&state);
+            states.push(state);
+        }
+        for n in [0, 1, 2, 10, 999, 2000] {
+            assert_eq!(nth_state(3, step, n), states[n]);
+        }
+        let cycle = find_cycle(3, step);
+        let n = 1_000_000_000_000;
+        assert_eq!(nth_state(3, step, n), states[cycle.earliest_equivalent(n)]);
+
+        // A counter never repeats, but nth_state stops at n.
+        assert_eq!(nth_state(0u64, |x| x + 1, 12345), 12345);
+    }
+
+    quickcheck! {
+        fn prop_matches_naive(initial: u8, multiplier: u8, increment: u8) -> bool {
+            let step = |x: &u64| (x * u64::from(multiplier) + u64::from(increment)) % 251;
+            find_cycle(u64::from(initial), step) == naive_cycle(u64::from(initial), step)
+        }
+    }
+}
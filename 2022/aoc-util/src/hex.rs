@@ -0,0 +1,361 @@
+//! Hexagonal grids, as in 2020 day 24: "pointy-topped" hexes, with rows running east–west, so a
+//! hex's neighbors are to the e, se, sw, w, nw and ne.
+//!
+//! [`Axial`] coordinates are the ones to store; [`Cube`] coordinates make the arithmetic
+//! symmetric. An [`Axial`] converts to and from a [`FreeCoord`], so a [`SparseMap`] can hold a hex
+//! grid, and [`Axial::neighbors`] is what a
+//! [`SetAutomaton`](crate::automaton::SetAutomaton) needs to step one.
+//!
+//! [`SparseMap`]: crate::sparse::SparseMap
+
+use std::convert::TryFrom;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+use crate::map::FreeCoord;
+
+/// The six directions from a hex to its neighbors, clockwise from east.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum HexDir {
+    E,
+    Se,
+    Sw,
+    W,
+    Nw,
+    Ne,
+}
+
+impl HexDir {
+    pub const ALL: [HexDir; 6] =
+        [HexDir::E, HexDir::Se, HexDir::Sw, HexDir::W, HexDir::Nw, HexDir::Ne];
+
+    /// The move, in axial coordinates, one step in this direction.
+    pub fn offset(self) -> Axial {
+        let (q, r) = match self {
+            HexDir::E => (1, 0),
+            HexDir::Se => (0, 1),
+            HexDir::Sw => (-1, 1),
+            HexDir::W => (-1, 0),
+            HexDir::Nw => (0, -1),
+            HexDir::Ne => (1, -1),
+        };
+        Axial { q, r }
+    }
+
+    /// The direction 60° clockwise of this one.
+    pub fn turn_cw(self) -> HexDir {
+        HexDir::ALL[(self.index() + 1) % 6]
+    }
+
+    /// The direction 60° counterclockwise of this one.
+    pub fn turn_ccw(self) -> HexDir {
+        HexDir::ALL[(self.index() + 5) % 6]
+    }
+
+    pub fn opposite(self) -> HexDir {
+        HexDir::ALL[(self.index() + 3) % 6]
+    }
+
+    fn index(self) -> usize {
+        HexDir::ALL.iter().position(|d| *d == self).unwrap()
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            HexDir::E => "e",
+            HexDir::Se => "se",
+            HexDir::Sw => "sw",
+            HexDir::W => "w",
+            HexDir::Nw => "nw",
+            HexDir::Ne => "ne",
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("not a hex direction: {0:?}")]
+pub struct ParseHexDirError(String);
+
+impl FromStr for HexDir {
+    type Err = ParseHexDirError;
+
+    fn from_str(s: &str) -> Result<HexDir, ParseHexDirError> {
+        HexDir::ALL
+            .iter()
+            .copied()
+            .find(|d| d.name() == s)
+            .ok_or_else(|| ParseHexDirError(s.to_owned()))
+    }
+}
+
+/// Parse a run of directions with no separators between them, like `"esenee"`.
+pub fn parse_dirs(mut s: &str) -> Result<Vec<HexDir>, ParseHexDirError> {
+    // The two-letter names first, so that "se" isn't read as an "s" we don't know.
+    const BY_LENGTH: [HexDir; 6] =
+        [HexDir::Se, HexDir::Sw, HexDir::Nw, HexDir::Ne, HexDir::E, HexDir::W];
+    let mut dirs = Vec::new();
+    while !s.is_empty() {
+        let (dir, rest) = BY_LENGTH
+            .iter()
+            .find_map(|&d| s.strip_prefix(d.name()).map(|rest| (d, rest)))
+            .ok_or_else(|| ParseHexDirError(s.chars().take(2).collect()))?;
+        dirs.push(dir);
+        s = rest;
+    }
+    Ok(dirs)
+}
+
+/// A hex, in axial coordinates: `q` increases to the east, `r` to the southeast.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Axial {
+    pub q: i64,
+    pub r: i64,
+}
+
+impl Axial {
+    pub const ORIGIN: Axial = Axial { q: 0, r: 0 };
+
+    pub fn new(q: i64, r: i64) -> Axial {
+        Axial { q, r }
+    }
+
+    /// The hex reached by following `dirs` from this one.
+    pub fn walk<I: IntoIterator<Item = HexDir>>(self, dirs: I) -> Axial {
+        dirs.into_iter().fold(self, |h, d| h.neighbor(d))
+    }
+
+    pub fn neighbor(self, dir: HexDir) -> Axial {
+        self + dir.offset()
+    }
+
+    /// The six adjacent hexes, clockwise from east.
+    pub fn neighbors(&self) -> impl Iterator<Item = Axial> {
+        let center = *self;
+        HexDir::ALL.iter().map(move |d| center.neighbor(*d))
+    }
+
+    /// The number of steps between two hexes.
+    pub fn distance(&self, other: &Axial) -> i64 {
+        Cube::from(*self).distance(&Cube::from(*other))
+    }
+
+    /// The `6 × radius` hexes exactly `radius` steps away, clockwise from the west-most corner.
+    /// (A radius of 0 is just this hex.)
+    pub fn ring(&self, radius: i64) -> impl Iterator<Item = Axial> {
+        assert!(0 <= radius, "negative ring radius {}", radius);
+        let start = *self + HexDir::W.offset() * radius;
+        let side = usize::try_from(radius).unwrap();
+        let steps = if radius == 0 { 1 } else { 6 * side };
+        (0..steps).scan(start, move |hex, i| {
+            let current = *hex;
+            // Each side runs 120° clockwise of the corner it starts from: NE from the W corner.
+            let dir = HexDir::ALL[(HexDir::Ne.index() + i / side.max(1)) % 6];
+            *hex = hex.neighbor(dir);
+            Some(current)
+        })
+    }
+
+    /// Every hex at most `radius` steps away, ring by ring outwards.
+    pub fn within(&self, radius: i64) -> impl Iterator<Item = Axial> {
+        let center = *self;
+        (0..=radius).flat_map(move |r| center.ring(r))
+    }
+}
+
+impl Add for Axial {
+    type Output = Axial;
+
+    fn add(self, rhs: Axial) -> Axial {
+        Axial {
+            q: self.q.checked_add(rhs.q).unwrap(),
+            r: self.r.checked_add(rhs.r).unwrap(),
+        }
+    }
+}
+
+impl Sub for Axial {
+    type Output = Axial;
+
+    fn sub(self, rhs: Axial) -> Axial {
+        Axial {
+            q: self.q.checked_sub(rhs.q).unwrap(),
+            r: self.r.checked_sub(rhs.r).unwrap(),
+        }
+    }
+}
+
+impl Mul<i64> for Axial {
+    type Output = Axial;
+
+    fn mul(self, rhs: i64) -> Axial {
+        Axial {
+            q: self.q.checked_mul(rhs).unwrap(),
+            r: self.r.checked_mul(rhs).unwrap(),
+        }
+    }
+}
+
+/// Store a hex in a square grid: `q` is `x` and `r` is `y`. (Rendered, the rows come out sheared.)
+impl From<Axial> for FreeCoord {
+    fn from(hex: Axial) -> FreeCoord {
+        FreeCoord { x: hex.q, y: hex.r }
+    }
+}
+
+impl From<FreeCoord> for Axial {
+    fn from(coord: FreeCoord) -> Axial {
+        Axial {
+            q: coord.x,
+            r: coord.y,
+        }
+    }
+}
+
+/// A hex, in cube coordinates: three axes at 120° to each other, with `q + r + s == 0`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Cube {
+    pub q: i64,
+    pub r: i64,
+    pub s: i64,
+}
+
+impl Cube {
+    pub fn distance(&self, other: &Cube) -> i64 {
+        let dq = (self.q - other.q).abs();
+        let dr = (self.r - other.r).abs();
+        let ds = (self.s - other.s).abs();
+        dq.max(dr).max(ds)
+    }
+
+    /// Rotate 60° clockwise about the origin.
+    pub fn rotate_cw(self) -> Cube {
+        Cube {
+            q: -self.r,
+            r: -self.s,
+            s: -self.q,
+        }
+    }
+
+    /// Rotate 60° counterclockwise about the origin.
+    pub fn rotate_ccw(self) -> Cube {
+        Cube {
+            q: -self.s,
+            r: -self.q,
+            s: -self.r,
+        }
+    }
+}
+
+impl From<Axial> for Cube {
+    fn from(hex: Axial) -> Cube {
+        Cube {
+            q: hex.q,
+            r: hex.r,
+            s: -hex.q - hex.r,
+        }
+    }
+}
+
+impl From<Cube> for Axial {
+    fn from(hex: Cube) -> Axial {
+        debug_assert_eq!(hex.q + hex.r + hex.s, 0);
+        Axial { q: hex.q, r: hex.r }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::automaton::{Automaton, SetAutomaton};
+    use crate::sparse::SparseMap;
+
+    #[test]
+    fn test_parse_and_walk() {
+        let dirs = parse_dirs("esenee").unwrap();
+        assert_eq!(dirs, [HexDir::E, HexDir::Se, HexDir::Ne, HexDir::E]);
+        assert_eq!(Axial::ORIGIN.walk(dirs), Axial::new(3, 0));
+        assert_eq!(Axial::ORIGIN.walk(parse_dirs("nwwswee").unwrap()), Axial::ORIGIN);
+        assert_eq!(Axial::ORIGIN.walk(parse_dirs("esew").unwrap()), Axial::new(0, 1));
+        assert!(parse_dirs("enx").is_err());
+        assert!(parse_dirs("en").is_err());
+        assert!(parse_dirs("é").is_err());
+        assert!(parse_dirs("né").is_err());
+        assert_eq!(parse_dirs("eéx").unwrap_err().to_string(), r#"not a hex direction: "éx""#);
+        assert_eq!(HexDir::Nw.turn_cw(), HexDir::Ne);
+        assert_eq!(HexDir::E.turn_ccw(), HexDir::Ne);
+        assert_eq!(HexDir::Se.opposite(), HexDir::Nw);
+    }
+
+    #[test]
+    fn test_distance_and_rings() {
+        let center = Axial::new(2, -1);
+        assert!(center.neighbors().all(|n| center.distance(&n) == 1));
+        for radius in 0..5 {
+            let ring = center.ring(radius).collect::<Vec<_>>();
+            assert_eq!(ring.len(), if radius == 0 { 1 } else { 6 * radius as usize });
+            assert_eq!(ring.iter().collect::<HashSet<_>>().len(), ring.len());
+            assert!(ring.iter().all(|h| center.distance(h) == radius));
+        }
+        // 1 + 6 + 12 + 18
+        assert_eq!(center.within(3).count(), 37);
+
+        let cube = Cube::from(Axial::new(3, -1));
+        assert_eq!(cube.rotate_cw().rotate_ccw(), cube);
+        let mut rotated = cube;
+        for _ in 0..6 {
+            rotated = rotated.rotate_cw();
+            assert_eq!(rotated.distance(&Cube::default()), 3);
+        }
+        assert_eq!(rotated, cube);
+        assert_eq!(Axial::from(Cube::from(HexDir::E.offset()).rotate_cw()), HexDir::Se.offset());
+    }
+
+    const TILES: &[&str] = &[
+        "sesenwnenenewseeswwswswwnenewsewsw",
+        "neeenesenwnwwswnenewnwwsewnenwseswesw",
+        "seswneswswsenwwnwse",
+        "nwnwneseeswswnenewneswwnewseswneseene",
+        "swweswneswnenwsewnwneneseenw",
+        "eesenwseswswnenwswnwnwsewwnwsene",
+        "sewnenenenesenwsewnenwwwse",
+        "wenwwweseeeweswwwnwwe",
+        "wsweesenenewnwwnwsenewsenwwsesesenwne",
+        "neeswseenwwswnwswswnw",
+        "nenwswwsewswnenenewsenwsenwnesesenew",
+        "enewnwewneswsewnwswenweswnenwsenwsw",
+        "sweneswneswneneenwnewenewwneswswnese",
+        "swwesenesewenwneswnwwneseswwne",
+        "enesenwswwswneneswsenwnewswseenwsese",
+        "wnwnesenesenenwwnenwsewesewsesesew",
+        "nenewswnwewswnenesenwnesewesw",
+        "eneswnwswnwsenenwnwnwwseeswneewsenese",
+        "neswnwewnwnwseenwseesewsenwsweewe",
+        "wseweeenwnesenwwwswnew",
+    ];
+
+    #[test]
+    fn test_lobby_layout() {
+        // 2020 day 24: flip the tiles the directions lead to...
+        let mut floor = SparseMap::new(false);
+        for line in TILES {
+            let tile = Axial::ORIGIN.walk(parse_dirs(line).unwrap()).into();
+            let black = *floor.get(tile);
+            floor.set(tile, !black);
+        }
+        assert_eq!(floor.len(), 10);
+
+        // ... then let them flip themselves for 100 days.
+        let black = floor.iter().map(|(c, _)| Axial::from(c));
+        let mut art = SetAutomaton::new(
+            black,
+            |h: &Axial| h.neighbors(),
+            |black, n| n == 2 || (black && n == 1),
+        );
+        art.step();
+        assert_eq!(art.active().len(), 15);
+        art.run(99);
+        assert_eq!(art.active().len(), 2208);
+    }
+}
@@ -0,0 +1,217 @@
+//! Writing grids out as images, for when they're too big to make sense of in a terminal.
+//!
+//! [`Map::write_image`](crate::map::Map::write_image) writes a pixel per tile, as PPM, or (with
+//! the `png` feature) PNG. [`Svg`] draws dots and paths on an unbounded grid.
+
+use std::fmt::{self, Write as _};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::map::FreeCoord;
+use crate::sparse::Bounds;
+
+/// A color, as red, green and blue.
+pub type Rgb = [u8; 3];
+
+/// Write `pixels`, row by row, as an image. The format is picked from the path's extension:
+/// `.ppm`, or `.png` if the `png` feature is on. If it's neither, the file is left alone.
+pub fn write_rgb(path: &Path, width: usize, height: usize, pixels: &[Rgb]) -> anyhow::Result<()> {
+    assert_eq!(pixels.len(), width * height);
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let format = match extension.to_ascii_lowercase().as_str() {
+        "ppm" => Format::Ppm,
+        #[cfg(feature = "png")]
+        "png" => Format::Png,
+        #[cfg(not(feature = "png"))]
+        "png" => anyhow::bail!("writing PNGs needs aoc-util's `png` feature"),
+        _ => anyhow::bail!("don't know how to write a {:?} image: {}", extension, path.display()),
+    };
+    let file = File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    match format {
+        Format::Ppm => write_ppm(&mut out, width, height, pixels)?,
+        #[cfg(feature = "png")]
+        Format::Png => write_png(&mut out, width, height, pixels)?,
+    }
+    out.flush().with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+enum Format {
+    Ppm,
+    #[cfg(feature = "png")]
+    Png,
+}
+
+/// Write a binary ("P6") PPM.
+pub fn write_ppm<W: Write>(
+    out: &mut W,
+    width: usize,
+    height: usize,
+    pixels: &[Rgb],
+) -> std::io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+    for pixel in pixels {
+        out.write_all(pixel)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "png")]
+pub fn write_png<W: Write>(
+    out: &mut W,
+    width: usize,
+    height: usize,
+    pixels: &[Rgb],
+) -> anyhow::Result<()> {
+    use std::convert::TryFrom;
+
+    let mut encoder = png::Encoder::new(out, u32::try_from(width)?, u32::try_from(height)?);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels.concat())?;
+    writer.finish()?;
+    Ok(())
+}
+
+const XMLNS: &str = "http://www.w3.org/2000/svg";
+
+/// An SVG drawing of dots and paths on a grid, where each cell is a unit square. The drawing's
+/// bounds grow to fit whatever is drawn.
+///
+/// ```
+/// use aoc_util::image::Svg;
+/// use aoc_util::map::FreeCoord;
+///
+/// let mut svg = Svg::new();
+/// svg.dots(vec![FreeCoord { x: 0, y: 0 }, FreeCoord { x: 3, y: 2 }], "black");
+/// svg.path(vec![FreeCoord { x: 0, y: 0 }, FreeCoord { x: 3, y: 0 }], "red");
+/// assert!(svg.to_string().starts_with("<svg"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Svg {
+    bounds: Option<Bounds>,
+    shapes: String,
+}
+
+impl Svg {
+    pub fn new() -> Svg {
+        Svg::default()
+    }
+
+    /// Fill each of `dots`' cells with `color` (any CSS color).
+    pub fn dots<I: IntoIterator<Item = FreeCoord>>(&mut self, dots: I, color: &str) -> &mut Svg {
+        let _ = writeln!(self.shapes, r#"<g fill="{}">"#, color);
+        for dot in dots {
+            self.grow_to(dot);
+            let _ = writeln!(
+                self.shapes,
+                r#"<rect x="{}" y="{}" width="1" height="1"/>"#,
+                dot.x, dot.y
+            );
+        }
+        self.shapes.push_str("</g>\n");
+        self
+    }
+
+    /// Draw a line in `color` through the centers of the cells of `path`, in order.
+    pub fn path<I: IntoIterator<Item = FreeCoord>>(&mut self, path: I, color: &str) -> &mut Svg {
+        let mut points = Vec::new();
+        for cell in path {
+            self.grow_to(cell);
+            points.push(format!("{},{}", cell.x as f64 + 0.5, cell.y as f64 + 0.5));
+        }
+        let _ = writeln!(
+            self.shapes,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="0.3"/>"#,
+            points.join(" "),
+            color
+        );
+        self
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, self.to_string())
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    fn grow_to(&mut self, cell: FreeCoord) {
+        self.bounds = Some(match self.bounds {
+            Some(b) => b.grow_to(cell),
+            None => Bounds::point(cell),
+        });
+    }
+}
+
+impl fmt::Display for Svg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bounds = self.bounds.unwrap_or_else(|| Bounds::point(FreeCoord { x: 0, y: 0 }));
+        let (width, height) = (bounds.width(), bounds.height());
+        // Scale small grids up, so they don't open as a speck; big ones get a pixel per cell.
+        let scale = (1000 / width.max(height)).clamp(1, 20);
+        writeln!(
+            f,
+            r#"<svg xmlns="{}" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            XMLNS,
+            width * scale,
+            height * scale,
+            bounds.min.x,
+            bounds.min.y,
+            width,
+            height
+        )?;
+        f.write_str(&self.shapes)?;
+        f.write_str("</svg>\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::map::Map;
+
+    #[test]
+    fn test_ppm() {
+        let mut out = Vec::new();
+        write_ppm(&mut out, 2, 1, &[[255, 0, 0], [0, 0, 255]]).unwrap();
+        assert_eq!(out, b"P6\n2 1\n255\n\xff\x00\x00\x00\x00\xff");
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        // Something's already there, and shouldn't be clobbered by a write that can't happen.
+        let path = std::env::temp_dir().join(format!("aoc-util-{}.jpg", std::process::id()));
+        fs::write(&path, "not yours").unwrap();
+        let map = Map::from_rows(vec![vec![true, false]]);
+        let err = map.write_image(&path, |&t| if t { [255; 3] } else { [0; 3] }).unwrap_err();
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(err.to_string().starts_with(r#"don't know how to write a "jpg" image"#));
+        assert_eq!(contents, "not yours");
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png() {
+        let mut out = Vec::new();
+        write_png(&mut out, 2, 1, &[[255, 0, 0], [0, 0, 255]]).unwrap();
+        assert!(out.starts_with(b"\x89PNG\r\n\x1a\n"));
+    }
+
+    #[test]
+    fn test_svg() {
+        let c = |x, y| FreeCoord { x, y };
+        let mut svg = Svg::new();
+        svg.dots(vec![c(-1, 0), c(2, 3)], "black").path(vec![c(0, 0), c(1, 0)], "red");
+        let svg = svg.to_string();
+        assert!(svg.contains(r#"width="80" height="80" viewBox="-1 0 4 4""#));
+        assert!(svg.contains(r#"<rect x="2" y="3" width="1" height="1"/>"#));
+        assert!(svg.contains(r#"points="0.5,0.5 1.5,0.5""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
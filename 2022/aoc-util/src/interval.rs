@@ -0,0 +1,283 @@
+//! Intervals of integers, and sets of them.
+//!
+//! An [`Interval`] is half-open, `start..end`, which keeps the arithmetic free of `+ 1`s; AoC's
+//! ranges, like 2022 day 4's `2-4`, are inclusive, so build those with [`Interval::inclusive`].
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Add, Sub};
+
+/// What an interval can be made of: anything ordered with a 1, like the integer types.
+pub trait Endpoint: Copy + Ord + Add<Output = Self> + Sub<Output = Self> + From<u8> {}
+
+impl<T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8>> Endpoint for T {}
+
+/// The values from `start` up to, but not including, `end`. Empty if `end <= start`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Endpoint> Interval<T> {
+    pub fn new(start: T, end: T) -> Interval<T> {
+        Interval { start, end }
+    }
+
+    /// The values from `first` to `last`, both included.
+    pub fn inclusive(first: T, last: T) -> Interval<T> {
+        Interval {
+            start: first,
+            end: last + T::from(1),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    /// The number of values in the interval.
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::from(0)
+        } else {
+            self.end - self.start
+        }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// Whether every value in `other` is in this interval. (The empty interval is in every one.)
+    pub fn contains_interval(&self, other: &Interval<T>) -> bool {
+        other.is_empty() || (self.start <= other.start && other.end <= self.end)
+    }
+
+    /// Whether the intervals have any value in common.
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The values in both intervals, or `None` if there aren't any.
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        let i = Interval {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
+        };
+        if i.is_empty() {
+            None
+        } else {
+            Some(i)
+        }
+    }
+
+    /// The values in this interval but not in `other`: up to two intervals, one either side of it.
+    pub fn subtract(&self, other: &Interval<T>) -> impl Iterator<Item = Interval<T>> {
+        let (below, above) = match self.intersection(other) {
+            None => (Some(*self), None),
+            Some(cut) => (
+                Some(Interval::new(self.start, cut.start)),
+                Some(Interval::new(cut.end, self.end)),
+            ),
+        };
+        below.into_iter().chain(above).filter(|i| !i.is_empty())
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Interval<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// A set of values, stored as the fewest intervals that cover them.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct IntervalSet<T> {
+    /// Sorted, non-empty, and with gaps between them.
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Add every value in `interval`.
+    pub fn insert(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        // Everything from `first` on touches or overlaps the new interval, up to `last`.
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+        let mut merged = interval;
+        if first < last {
+            merged.start = merged.start.min(self.intervals[first].start);
+            merged.end = merged.end.max(self.intervals[last - 1].end);
+        }
+        self.intervals.splice(first..last, std::iter::once(merged));
+    }
+
+    /// Remove every value in `interval`.
+    pub fn remove(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        let first = self.intervals.partition_point(|i| i.end <= interval.start);
+        let last = self.intervals.partition_point(|i| i.start < interval.end);
+        let remains = self.intervals[first..last]
+            .iter()
+            .flat_map(|i| i.subtract(&interval))
+            .collect::<Vec<_>>();
+        self.intervals.splice(first..last, remains);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self.intervals.partition_point(|i| i.end <= value);
+        self.intervals.get(idx).is_some_and(|i| i.contains(value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The number of values in the set.
+    pub fn len(&self) -> T {
+        self.intervals.iter().fold(T::from(0), |total, i| total + i.len())
+    }
+
+    /// The intervals making up the set, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+
+    /// The values in both sets.
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = IntervalSet::new();
+        for a in self.intervals.iter() {
+            for b in other.intervals.iter() {
+                if let Some(i) = a.intersection(b) {
+                    result.intervals.push(i);
+                }
+            }
+        }
+        result
+    }
+
+    /// The values in this set but not in `other`.
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = self.clone();
+        for i in other.intervals.iter() {
+            result.remove(*i);
+        }
+        result
+    }
+}
+
+impl<T: Endpoint> Default for IntervalSet<T> {
+    fn default() -> IntervalSet<T> {
+        IntervalSet::new()
+    }
+}
+
+impl<T: Endpoint> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> IntervalSet<T> {
+        let mut set = IntervalSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Endpoint> Extend<Interval<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = Interval<T>>>(&mut self, iter: I) {
+        for interval in iter {
+            self.insert(interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    use quickcheck::quickcheck;
+
+    #[test]
+    fn test_interval() {
+        // 2022 day 4's pairs: 2-8 contains 3-7; 5-7 and 7-9 overlap; 2-4 and 6-8 don't.
+        let i = Interval::inclusive;
+        assert!(i(2, 8).contains_interval(&i(3, 7)));
+        assert!(!i(3, 7).contains_interval(&i(2, 8)));
+        assert!(i(5, 7).overlaps(&i(7, 9)));
+        assert!(!i(2, 4).overlaps(&i(6, 8)));
+        assert_eq!(i(5, 7).intersection(&i(7, 9)), Some(i(7, 7)));
+        assert_eq!(i(2, 8).len(), 7);
+        assert_eq!(Interval::new(3, 1).len(), 0);
+
+        let parts = i(0, 9).subtract(&i(3, 5)).collect::<Vec<_>>();
+        assert_eq!(parts, [Interval::new(0, 3), Interval::new(6, 10)]);
+        assert_eq!(i(0, 9).subtract(&i(-5, 20)).count(), 0);
+        assert_eq!(i(0, 9).subtract(&i(20, 30)).collect::<Vec<_>>(), [i(0, 9)]);
+    }
+
+    #[test]
+    fn test_interval_set() {
+        // 2020 day 16's ticket rules: is a value valid for any field?
+        let rules = vec![(1, 3), (5, 7), (6, 11), (33, 44), (13, 40), (45, 50)];
+        let valid = rules
+            .into_iter()
+            .map(|(a, b)| Interval::inclusive(a, b))
+            .collect::<IntervalSet<_>>();
+        assert_eq!(valid.iter().copied().collect::<Vec<_>>(), [
+            Interval::new(1, 4),
+            Interval::new(5, 12),
+            Interval::new(13, 51),
+        ]);
+        let invalid = [7, 3, 47, 40, 4, 50, 55, 2, 20, 38, 6, 12]
+            .iter()
+            .filter(|&&v| !valid.contains(v))
+            .sum::<i64>();
+        assert_eq!(invalid, 4 + 55 + 12);
+
+        let mut set = valid.clone();
+        set.remove(Interval::new(2, 40));
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), [
+            Interval::new(1, 2),
+            Interval::new(40, 51),
+        ]);
+        assert_eq!(set.len(), 12);
+        let other = IntervalSet::from_iter(vec![Interval::new(0, 6), Interval::new(45, 100)]);
+        assert_eq!(valid.intersection(&other).len(), 3 + 1 + 6);
+        assert_eq!(valid.difference(&other).len(), valid.len() - 10);
+    }
+
+    quickcheck! {
+        fn prop_set_matches_reference(ops: Vec<(bool, i8, u8)>, probe: i8) -> bool {
+            let mut set = IntervalSet::new();
+            let mut reference = std::collections::BTreeSet::new();
+            for (insert, start, len) in ops {
+                let (start, end) = (i32::from(start), i32::from(start) + i32::from(len % 16));
+                let interval = Interval::new(start, end);
+                if insert {
+                    set.insert(interval);
+                    reference.extend(start..end);
+                } else {
+                    set.remove(interval);
+                    for v in start..end {
+                        reference.remove(&v);
+                    }
+                }
+            }
+            let gaps_between = set.iter().zip(set.iter().skip(1)).all(|(a, b)| a.end < b.start);
+            gaps_between
+                && set.iter().all(|i| !i.is_empty())
+                && set.len() == i32::try_from(reference.len()).unwrap()
+                && set.contains(i32::from(probe)) == reference.contains(&i32::from(probe))
+        }
+    }
+}
@@ -1,18 +1,35 @@
-use std::collections::HashMap;
 use std::fmt::{Debug, Display};
 use std::fs::File;
-use std::hash::Hash;
 use std::io::{self, BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use structopt::StructOpt;
 
-mod autofetch;
+use crate::term::{Styled, TermColor};
+
+#[cfg(feature = "autofetch")]
+pub mod autofetch;
+pub mod automaton;
+pub mod counter;
+pub mod cuboid;
+pub mod cycle;
+pub mod hex;
+pub mod image;
+pub mod interval;
 pub mod map;
+pub mod memo;
+pub mod numtheory;
+pub mod octree;
+pub mod ocr;
+pub mod point;
+pub mod rotation;
+pub mod search;
+pub mod sparse;
 pub mod term;
 mod text;
 pub mod to_tuple;
+pub mod viz;
 
 pub mod prelude {
     use std::collections;
@@ -26,6 +43,7 @@ pub mod prelude {
     pub use path::Path;
     pub use std::convert::{TryFrom, TryInto};
 
+    pub use crate::counter::Counter;
     pub use crate::to_tuple::{IterExtToTuple, ToTuple};
 }
 
@@ -121,14 +139,6 @@ pub fn comma_try_split<F: Fn(&str) -> Result<T, E>, T, E>(s: &str, f: F) -> Resu
     s.split(',').map(f).collect::<Result<Vec<T>, E>>()
 }
 
-pub fn histogram<T: Copy + Eq + Hash>(input: impl IntoIterator<Item = T>) -> HashMap<T, usize> {
-    let mut counts = HashMap::new();
-    for item in input.into_iter() {
-        *counts.entry(item).or_insert(0) += 1;
-    }
-    counts
-}
-
 /*
 pub fn one_line_of_ints(path: &Path) -> anyhow::Result<Vec<i64>> {
     let file = BufReader::new(File::open(path)?);
@@ -142,6 +152,19 @@ pub fn one_line_of_ints(path: &Path) -> anyhow::Result<Vec<i64>> {
 }
 */
 
+fn print_passed(part: &str) {
+    let green = |s| Styled::new(s).fg(TermColor::BrightGreen);
+    println!("{}{}{}", green("PART "), green(part).underlined(), green(" TEST VEC PASSED!"));
+}
+
+fn print_failed<T: Display>(part: &str, actual: T, expected: T) {
+    let message = format!(
+        "PART {} TEST VEC FAILED! ({} (actual) != {} (expected))",
+        part, actual, expected
+    );
+    println!("{}", Styled::new(message).bold().fg(TermColor::BrightRed));
+}
+
 #[derive(StructOpt)]
 pub struct Args {
     #[structopt(long)]
@@ -150,6 +173,26 @@ pub struct Args {
     test_input_b: Option<PathBuf>,
     #[structopt(long)]
     input: Option<PathBuf>,
+    /// Animate the solution, for solutions that draw frames with `aoc::viz`.
+    #[structopt(long)]
+    viz: bool,
+    /// The most frames a second to animate at, with `--viz`.
+    #[structopt(long, default_value = "30", validator = validate_fps)]
+    fps: f64,
+}
+
+/// A frame rate has to be positive, and not so small that a frame lasts longer than a
+/// [`Duration`](std::time::Duration) can.
+fn validate_fps(fps: String) -> Result<(), String> {
+    let valid = match fps.parse::<f64>() {
+        Ok(fps) => 0. < fps && std::time::Duration::try_from_secs_f64(1. / fps).is_ok(),
+        Err(_) => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("the frame rate must be a positive number, not {:?}", fps))
+    }
 }
 
 pub fn main_stub<I, A, B, IT, IE, AT, BT>(
@@ -167,23 +210,17 @@ pub fn main_stub<I, A, B, IT, IE, AT, BT>(
     BT: Display + Eq,
 {
     let args = Args::from_args();
+    viz::init(viz::Viz::new(args.viz, args.fps));
 
-    match tokio::runtime::Runtime::new() {
-        Ok(runtime) => {
-            let result = runtime.block_on(autofetch::autofetch());
-            match result {
-                Ok(()) => (),
-                Err(err) => {
-                    eprintln!("\x1b[1;91mAuto-fetch failed:\x1b[0m {err}");
-                }
-            }
-        }
-        Err(err) => eprintln!("\x1b[1;91mAuto-fetch failed:\x1b[0m {err}"),
+    #[cfg(feature = "autofetch")]
+    if let Err(err) = autofetch::autofetch() {
+        let failed = Styled::new("Auto-fetch failed:").bold().fg(TermColor::BrightRed);
+        eprintln!("{} {err}", failed.on(term::Output::Stderr));
     }
 
     let test_input_a = args.test_input_a.as_deref().unwrap_or(Path::new("test-a"));
     if test_input_a.exists() {
-        println!("\x1b[96m── Part A: Test ────\x1b[0m");
+        println!("{}", Styled::new("── Part A: Test ────").fg(TermColor::BrightCyan));
         println!("Test input A exists. Running test…");
         let input = input_parser(&test_input_a.into()).expect("failed to parse test input");
         println!("(input parsed; running part A test…)");
@@ -191,12 +228,9 @@ pub fn main_stub<I, A, B, IT, IE, AT, BT>(
         match test_vec_a {
             Some(known_answer) => {
                 if part_a_answer == known_answer {
-                    println!("\x1b[92mPART \x1b[4mA\x1b[24m TEST VEC PASSED!\x1b[0m");
+                    print_passed("A");
                 } else {
-                    println!(
-                        "\x1b[1;91mPART A TEST VEC FAILED! ({} (actual) != {} (expected))\x1b[0m",
-                        part_a_answer, known_answer
-                    );
+                    print_failed("A", part_a_answer, known_answer);
                 }
             }
             None => {
@@ -207,7 +241,7 @@ pub fn main_stub<I, A, B, IT, IE, AT, BT>(
     }
     let test_input_b = args.test_input_b.as_deref().unwrap_or(Path::new("test-b"));
     if test_input_b.exists() {
-        println!("\x1b[96m── Part B: Test ────\x1b[0m");
+        println!("{}", Styled::new("── Part B: Test ────").fg(TermColor::BrightCyan));
         println!("Test input B exists. Running test…");
         let input = input_parser(&test_input_b.into()).expect("failed to parse test input");
         println!("(input parsed; running part B test…)");
@@ -215,12 +249,9 @@ pub fn main_stub<I, A, B, IT, IE, AT, BT>(
         match test_vec_b {
             Some(known_answer) => {
                 if part_b_answer == known_answer {
-                    println!("\x1b[92mPART \x1b[4mB\x1b[24m TEST VEC PASSED!\x1b[0m");
+                    print_passed("B");
                 } else {
-                    println!(
-                        "\x1b[1;91mPART B TEST VEC FAILED! ({} (actual) != {} (expected))\x1b[0m",
-                        part_b_answer, known_answer
-                    );
+                    print_failed("B", part_b_answer, known_answer);
                 }
             }
             None => {
@@ -230,7 +261,7 @@ pub fn main_stub<I, A, B, IT, IE, AT, BT>(
         }
     }
 
-    println!("\x1b[1m── Running on the real input ────\x1b[0m");
+    println!("{}", Styled::new("── Running on the real input ────").bold());
     let input_path = args.input.as_deref().unwrap_or(Path::new("input"));
     print!("(parsing the input file)");
     let input = match input_parser(&input_path.into()) {
@@ -302,10 +333,24 @@ pub enum HotParseError {
 
 #[cfg(test)]
 mod tests {
+    use structopt::StructOpt;
+
+    use super::Args;
+
     #[test]
     fn test_quick_regex_parser() {
         quick_regex_parser!(parser, "([0-9]+) (foo|bar)", { 1 => u8, 2 => String, });
         let result = parser("123 foo").unwrap();
         assert!(result == (123, "foo".to_owned()));
     }
+
+    #[test]
+    fn test_fps() {
+        let fps = |arg: &str| Args::from_iter_safe(["aoc", arg]).map(|args| args.fps);
+        assert_eq!(fps("--viz").unwrap(), 30.);
+        assert_eq!(fps("--fps=2.5").unwrap(), 2.5);
+        for bad in ["--fps=0", "--fps=-1", "--fps=NaN", "--fps=1e-300", "--fps=fast"] {
+            assert!(fps(bad).is_err(), "{} was accepted", bad);
+        }
+    }
 }
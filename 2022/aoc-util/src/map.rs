@@ -1,12 +1,15 @@
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Write};
 use std::path::Path;
 
+use crate::term::{Output, Styled, TermAttr, TermColor};
+
 /// A 2D map.
 ///
 /// Internal storage is row major.
-#[derive(Clone)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Map<T> {
     tiles: Vec<T>,
     width: usize,
@@ -26,6 +29,46 @@ impl<T: Copy> Map<T> {
 }
 
 impl<T> Map<T> {
+    /// Build a map from its rows.
+    ///
+    /// Panics if the rows are not all the same width.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Map<T> {
+        let height = rows.len();
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+        let mut tiles = Vec::with_capacity(width.checked_mul(height).unwrap());
+        for (row_idx, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                panic!("Row {}'s width was {}, but we expected {}.", row_idx, row.len(), width);
+            }
+            tiles.extend(row);
+        }
+        Map {
+            tiles,
+            width,
+            height,
+        }
+    }
+
+    /// Build a `width` × `height` map, with each tile being `f(x, y)`.
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(width: usize, height: usize, mut f: F) -> Map<T> {
+        let len = width.checked_mul(height).expect("width * height exceeded usize::MAX");
+        let mut tiles = Vec::with_capacity(len);
+        for y in 0..height {
+            for x in 0..width {
+                tiles.push(f(x, y));
+            }
+        }
+        Map {
+            tiles,
+            width,
+            height,
+        }
+    }
+
+    /// Parse a map from a file, with each character being a tile.
+    ///
+    /// Lines of different widths are an error. For maps with markers, characters that aren't
+    /// tiles, or ragged lines, see [`MapParser`].
     pub fn parse_filename_char_is_tile<F: Fn(char) -> T>(
         path: &Path,
         tile_parse: F,
@@ -42,8 +85,14 @@ impl<T> Map<T> {
             match width {
                 Some(w) if w == this_width => (),
                 Some(w) => {
-                    panic!("Line {}'s width was {}, but we expected {}.", line_idx.checked_add(1).unwrap(), this_width, w);
-                },
+                    let message = format!(
+                        "line {}'s width was {}, but we expected {}",
+                        line_idx.checked_add(1).unwrap(),
+                        this_width,
+                        w
+                    );
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                }
                 None => width = Some(this_width),
             }
             tiles.extend(row);
@@ -52,7 +101,7 @@ impl<T> Map<T> {
         let width = match width {
             Some(w) => w,
             None => {
-                assert!(tiles.len() == 0);
+                assert!(tiles.is_empty());
                 0
             }
         };
@@ -75,7 +124,7 @@ impl<T> Map<T> {
             type Item = (BoundCoord, &'a T);
 
             fn next(&mut self) -> Option<Self::Item> {
-                if self.map.height <= self.y {
+                if self.map.height <= self.y || self.map.width == 0 {
                     return None;
                 }
 
@@ -99,30 +148,38 @@ impl<T> Map<T> {
         TileIter {
             x: 0,
             y: 0,
-            map: &self,
+            map: self,
         }
     }
 
-    pub fn at<C: AsCoord>(&self, position: C) -> Option<&T> {
+    /// The index into `tiles` of the tile at `(x, y)`, if that's on the map.
+    fn index<C: AsCoord>(&self, position: C) -> Option<usize> {
         let (x, y) = position.as_coord();
-        let idx = x.checked_mul(y).expect("tile coordinate exceeded usize::MAX");
+        if self.width <= x || self.height <= y {
+            return None;
+        }
+        // Can't overflow: both are less than the dimensions, and width * height fits.
+        Some(y * self.width + x)
+    }
+
+    pub fn at<C: AsCoord>(&self, position: C) -> Option<&T> {
+        let idx = self.index(position)?;
         self.tiles.get(idx)
     }
 
     pub fn at_mut<C: AsCoord>(&mut self, position: C) -> Option<&mut T> {
-        let (x, y) = position.as_coord();
-        let idx = x.checked_mul(y).expect("tile coordinate exceeded usize::MAX");
+        let idx = self.index(position)?;
         self.tiles.get_mut(idx)
     }
 
     /// Iterate over rows of tiles on the map.
     ///
     /// This method exists to make it easy to write a double for loop over the tiles.
-    pub fn rows(&self) -> impl Iterator<Item = Row<T>> {
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_, T>> {
         (0..self.height).map(move |y| {
             Row {
                 y,
-                map: &self,
+                map: self,
             }
         })
     }
@@ -137,20 +194,404 @@ impl<T> Map<T> {
         self.height
     }
 
-    /*
+    /// Print the map to stdout, one character per tile.
     pub fn render_single_char<F: Fn(&T) -> char>(&self, df: F) {
-        for row in self.0.iter() {
-            for cell in row.iter() {
-                print!("{}", df(cell));
+        print!("{}", self.to_string_single_char(df));
+    }
+
+    /// The map as a string, one character per tile and a line per row.
+    pub fn to_string_single_char<F: Fn(&T) -> char>(&self, df: F) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            s.extend(row.tiles().map(|(_, t)| df(t)));
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Write the map as an image, a pixel per tile, colored by `tile_to_rgb`. The format is picked
+    /// from the path's extension: see [`image::write_rgb`](crate::image::write_rgb).
+    pub fn write_image<P, F>(&self, path: P, tile_to_rgb: F) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+        F: Fn(&T) -> crate::image::Rgb,
+    {
+        let pixels = self.tiles.iter().map(tile_to_rgb).collect::<Vec<_>>();
+        crate::image::write_rgb(path.as_ref(), self.width, self.height, &pixels)
+    }
+}
+
+/// Transforms of the whole map, each of which returns a new map.
+impl<T: Clone> Map<T> {
+    fn get(&self, x: usize, y: usize) -> T {
+        self.tiles[y * self.width + x].clone()
+    }
+
+    /// Swap the axes: the tile at `(x, y)` moves to `(y, x)`.
+    pub fn transpose(&self) -> Map<T> {
+        Map::from_fn(self.height, self.width, |x, y| self.get(y, x))
+    }
+
+    /// Rotate the map a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Map<T> {
+        let h = self.height;
+        Map::from_fn(self.height, self.width, |x, y| self.get(y, h - 1 - x))
+    }
+
+    /// Rotate the map a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> Map<T> {
+        let w = self.width;
+        Map::from_fn(self.height, self.width, |x, y| self.get(w - 1 - y, x))
+    }
+
+    /// Rotate the map a half turn.
+    pub fn rotate_180(&self) -> Map<T> {
+        let (w, h) = (self.width, self.height);
+        Map::from_fn(w, h, |x, y| self.get(w - 1 - x, h - 1 - y))
+    }
+
+    /// Mirror the map left-to-right.
+    pub fn flip_h(&self) -> Map<T> {
+        let w = self.width;
+        Map::from_fn(self.width, self.height, |x, y| self.get(w - 1 - x, y))
+    }
+
+    /// Mirror the map top-to-bottom.
+    pub fn flip_v(&self) -> Map<T> {
+        let h = self.height;
+        Map::from_fn(self.width, self.height, |x, y| self.get(x, h - 1 - y))
+    }
+
+    /// All 8 orientations of the map (the dihedral group): the 4 rotations (starting with the map
+    /// as it is, then turning clockwise), followed by the 4 rotations of the map flipped
+    /// left-to-right.
+    pub fn orientations(&self) -> impl Iterator<Item = Map<T>> + '_ {
+        (0..8).map(move |n| {
+            let mut map = if n < 4 { self.clone() } else { self.flip_h() };
+            for _ in 0..n % 4 {
+                map = map.rotate_cw();
+            }
+            map
+        })
+    }
+
+    /// Repeat the map `nx` times across and `ny` times down.
+    ///
+    /// `f(tile, tile_x, tile_y)` gives the tile for the copy at `(tile_x, tile_y)` (in copies, not
+    /// tiles), from the original tile.
+    pub fn tile<F: Fn(&T, usize, usize) -> T>(&self, nx: usize, ny: usize, f: F) -> Map<T> {
+        let (w, h) = (self.width, self.height);
+        let width = w.checked_mul(nx).expect("tiled width exceeded usize::MAX");
+        let height = h.checked_mul(ny).expect("tiled height exceeded usize::MAX");
+        Map::from_fn(width, height, |x, y| f(&self.tiles[(y % h) * w + x % w], x / w, y / h))
+    }
+
+    /// The part of the map inside `rect`, or `None` if `rect` isn't entirely on the map.
+    pub fn crop(&self, rect: Rect) -> Option<Map<T>> {
+        let right = rect.x.checked_add(rect.width)?;
+        let bottom = rect.y.checked_add(rect.height)?;
+        if self.width < right || self.height < bottom {
+            return None;
+        }
+        Some(Map::from_fn(rect.width, rect.height, |x, y| self.get(rect.x + x, rect.y + y)))
+    }
+
+    /// Grow the map by `n` tiles on every side, filling the new tiles with `fill`.
+    pub fn pad(&self, n: usize, fill: T) -> Map<T> {
+        let width = self.width.checked_add(n.checked_mul(2).unwrap()).unwrap();
+        let height = self.height.checked_add(n.checked_mul(2).unwrap()).unwrap();
+        Map::from_fn(width, height, |x, y| {
+            let inside = n <= x && x < n + self.width && n <= y && y < n + self.height;
+            if inside {
+                self.get(x - n, y - n)
+            } else {
+                fill.clone()
+            }
+        })
+    }
+}
+
+/// Rendering in color, for tiles that know how to show themselves.
+impl<T: DisplayTile> Map<T> {
+    /// Write the map, a line per row, with each tile styled as [`DisplayTile::to_term`] says, if
+    /// `output` is to be styled at all.
+    pub fn render<W: Write>(&self, out: &mut W, output: Output) -> io::Result<()> {
+        self.render_highlighted(out, &[], output)
+    }
+
+    /// Write the map as [`Map::render`] does, but with the tiles in each of `highlights` restyled.
+    /// Where highlights overlap, the later one wins.
+    pub fn render_highlighted<W: Write>(
+        &self,
+        out: &mut W,
+        highlights: &[Highlight<'_>],
+        output: Output,
+    ) -> io::Result<()> {
+        let mut overlay = HashMap::new();
+        for highlight in highlights {
+            for &coord in highlight.tiles.iter() {
+                overlay.insert(coord, highlight);
+            }
+        }
+        for row in self.rows() {
+            for (coord, tile) in row.tiles() {
+                let (mut glyph, mut attr) = tile.to_term();
+                if let Some(highlight) = overlay.get(&coord.as_coord()) {
+                    glyph = highlight.glyph.unwrap_or(glyph);
+                    attr = Some(highlight.attr);
+                }
+                match attr {
+                    Some(attr) => write!(out, "{}", Styled::new(glyph).style(attr).on(output))?,
+                    None => write!(out, "{}", glyph)?,
+                }
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// How a tile looks on a terminal.
+pub trait DisplayTile {
+    /// The text for the tile (usually a single character), and how to style it, if at all.
+    fn to_term(&self) -> (&str, Option<TermAttr>);
+}
+
+/// A wall (`#`) or open space (`.`).
+impl DisplayTile for bool {
+    fn to_term(&self) -> (&str, Option<TermAttr>) {
+        if *self {
+            ("#", Some(TermAttr::color(TermColor::BrightWhite)))
+        } else {
+            (".", Some(TermAttr::color(TermColor::BrightBlack)))
+        }
+    }
+}
+
+/// Printable ASCII as itself, unstyled; anything else as `?`.
+impl DisplayTile for char {
+    fn to_term(&self) -> (&str, Option<TermAttr>) {
+        const ASCII: &str = concat!(
+            " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`",
+            "abcdefghijklmnopqrstuvwxyz{|}~",
+        );
+        let glyph = match *self {
+            ' '..='~' => {
+                let idx = usize::from(*self as u8 - b' ');
+                &ASCII[idx..idx + 1]
+            }
+            _ => "?",
+        };
+        (glyph, None)
+    }
+}
+
+/// A digit, like a height or an energy level, colored from blue (0) to red (9). Anything larger
+/// is a bold `+`.
+impl DisplayTile for u8 {
+    fn to_term(&self) -> (&str, Option<TermAttr>) {
+        const DIGITS: &[&str] = &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        let color = match *self {
+            0..=1 => TermColor::Blue,
+            2..=3 => TermColor::Cyan,
+            4..=5 => TermColor::Green,
+            6..=7 => TermColor::Yellow,
+            _ => TermColor::Red,
+        };
+        match DIGITS.get(usize::from(*self)) {
+            Some(digit) => (digit, Some(TermAttr::color(color))),
+            None => {
+                let attr = TermAttr {
+                    bold: true,
+                    ..TermAttr::color(TermColor::BrightRed)
+                };
+                ("+", Some(attr))
             }
-            println!()
         }
     }
-    */
 }
 
-trait DisplayTile {
-    fn to_term(&self) -> (&str, Option<crate::term::TermAttr>);
+/// A set of tiles to pick out when rendering, like the path a search found.
+pub struct Highlight<'a> {
+    tiles: Vec<(usize, usize)>,
+    attr: TermAttr,
+    glyph: Option<&'a str>,
+}
+
+impl<'a> Highlight<'a> {
+    /// Style `tiles` with `attr`, in place of their own styling.
+    pub fn new<C: AsCoord, I: IntoIterator<Item = C>>(tiles: I, attr: TermAttr) -> Highlight<'a> {
+        Highlight {
+            tiles: tiles.into_iter().map(|c| c.as_coord()).collect(),
+            attr,
+            glyph: None,
+        }
+    }
+
+    /// Show the highlighted tiles as `glyph`, too.
+    pub fn with_glyph(mut self, glyph: &'a str) -> Highlight<'a> {
+        self.glyph = Some(glyph);
+        self
+    }
+}
+
+/// Parses a map from text, for maps where characters need sorting out: some are tiles, some are
+/// markers (like the start `S` and end `E`) whose coordinates are wanted too, and some lines might
+/// be shorter than others.
+///
+/// ```
+/// use aoc_util::map::MapParser;
+///
+/// let parsed = MapParser::new()
+///     .tile('#', false)
+///     .tile('.', true)
+///     .marker('S', true)
+///     .marker('E', true)
+///     .parse_str("#####\n#S.E#\n#####\n")
+///     .unwrap();
+/// assert_eq!(parsed.map.width(), 5);
+/// assert_eq!(parsed.marker('S').map(|c| (c.x, c.y)), Some((1, 1)));
+/// ```
+pub struct MapParser<'a, T> {
+    parsers: Vec<Box<dyn Fn(char) -> T + 'a>>,
+    /// Which of `parsers` parses each character.
+    chars: HashMap<char, usize>,
+    markers: Vec<char>,
+    pad: Option<T>,
+}
+
+/// A map parsed by a [`MapParser`], and where its markers were.
+#[derive(Clone, Debug)]
+pub struct ParsedMap<T> {
+    pub map: Map<T>,
+    pub markers: HashMap<char, Vec<BoundCoord>>,
+}
+
+impl<T> ParsedMap<T> {
+    /// Where `marker` was; the first place, in reading order, if it was in more than one.
+    pub fn marker(&self, marker: char) -> Option<&BoundCoord> {
+        self.markers.get(&marker).and_then(|c| c.first())
+    }
+
+    /// Everywhere `marker` was, in reading order.
+    pub fn markers(&self, marker: char) -> &[BoundCoord] {
+        self.markers.get(&marker).map_or(&[], |c| c.as_slice())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MapParseError {
+    #[error("failed to read the map")]
+    Io(#[from] io::Error),
+    #[error("line {line}, column {column}: {ch:?} isn't a tile")]
+    UnknownChar {
+        line: usize,
+        column: usize,
+        ch: char,
+    },
+    #[error("line {line}'s width was {width}, but we expected {expected}")]
+    Ragged {
+        line: usize,
+        width: usize,
+        expected: usize,
+    },
+}
+
+impl<'a, T: Clone + 'a> MapParser<'a, T> {
+    pub fn new() -> MapParser<'a, T> {
+        MapParser {
+            parsers: Vec::new(),
+            chars: HashMap::new(),
+            markers: Vec::new(),
+            pad: None,
+        }
+    }
+
+    /// `ch` is `tile`.
+    pub fn tile(self, ch: char, tile: T) -> MapParser<'a, T> {
+        self.tiles(&ch.to_string(), move |_| tile.clone())
+    }
+
+    /// Each of `chars` is the tile `parse` makes of it, like `.tiles("ABCD", Amphipod::from)`.
+    pub fn tiles<F: Fn(char) -> T + 'a>(mut self, chars: &str, parse: F) -> MapParser<'a, T> {
+        let idx = self.parsers.len();
+        self.parsers.push(Box::new(parse));
+        self.chars.extend(chars.chars().map(|c| (c, idx)));
+        self
+    }
+
+    /// `ch` is `tile`, and its coordinates are recorded in [`ParsedMap::markers`].
+    pub fn marker(mut self, ch: char, tile: T) -> MapParser<'a, T> {
+        self.markers.push(ch);
+        self.tile(ch, tile)
+    }
+
+    /// Pad lines shorter than the longest with `tile`, rather than call them an error.
+    pub fn pad(mut self, tile: T) -> MapParser<'a, T> {
+        self.pad = Some(tile);
+        self
+    }
+
+    pub fn parse_file<P: AsRef<Path>>(&self, path: P) -> Result<ParsedMap<T>, MapParseError> {
+        self.parse_str(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse_str(&self, s: &str) -> Result<ParsedMap<T>, MapParseError> {
+        let mut rows = Vec::new();
+        let mut markers = Vec::new();
+        for (y, line) in s.lines().enumerate() {
+            let mut row = Vec::with_capacity(line.len());
+            for (x, ch) in line.chars().enumerate() {
+                let idx = self.chars.get(&ch).ok_or(MapParseError::UnknownChar {
+                    line: y + 1,
+                    column: x + 1,
+                    ch,
+                })?;
+                row.push(self.parsers[*idx](ch));
+                if self.markers.contains(&ch) {
+                    markers.push((ch, x, y));
+                }
+            }
+            rows.push(row);
+        }
+
+        let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        for (y, row) in rows.iter_mut().enumerate() {
+            match &self.pad {
+                Some(pad) => row.resize(width, pad.clone()),
+                None if row.len() != width => {
+                    return Err(MapParseError::Ragged {
+                        line: y + 1,
+                        width: row.len(),
+                        expected: width,
+                    });
+                }
+                None => (),
+            }
+        }
+
+        let map = Map::from_rows(rows);
+        let mut found = HashMap::<char, Vec<BoundCoord>>::new();
+        for (ch, x, y) in markers {
+            let coord = BoundCoord {
+                width: map.width(),
+                height: map.height(),
+                x,
+                y,
+            };
+            found.entry(ch).or_default().push(coord);
+        }
+        Ok(ParsedMap {
+            map,
+            markers: found,
+        })
+    }
+}
+
+impl<'a, T: Clone + 'a> Default for MapParser<'a, T> {
+    fn default() -> MapParser<'a, T> {
+        MapParser::new()
+    }
 }
 
 /// A single row in a map, when iterating over rows with [`Map::rows`].
@@ -265,6 +706,15 @@ impl BoundCoord {
     }
 }
 
+/// An axis-aligned rectangle of tiles: `width` × `height` tiles, with its top left at `(x, y)`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
 const OFFSETS: &[(i8, i8)] = &[(-1, 0), (1, 0), (0, -1), (0, 1)];
 
 struct BoundCoordCardinals<'a>(&'a BoundCoord, u8);
@@ -340,41 +790,306 @@ impl Iterator for BoundCoordDiags<'_> {
     }
 }
 
-struct RowIter<'a, T>(
-    &'a Map<T>,
-    std::iter::Enumerate<std::slice::Iter<'a, Vec<T>>>,
-);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl<'a, T> Iterator for RowIter<'a, T> {
-    type Item = (usize, CellIter<'a, T>);
+    use quickcheck::quickcheck;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.1
-            .next()
-            .map(|(i, v)| (i, CellIter(self.0, i, v.iter().enumerate())))
+    /// A 4 × 3 map, with each tile holding its own coordinate.
+    fn coord_map() -> Map<(usize, usize)> {
+        Map::from_rows((0..3).map(|y| (0..4).map(|x| (x, y)).collect()).collect())
     }
-}
 
-struct CellIter<'a, T>(
-    &'a Map<T>,
-    usize,
-    std::iter::Enumerate<std::slice::Iter<'a, T>>,
-);
+    #[test]
+    fn test_at() {
+        let map = coord_map();
+        assert_eq!((map.width(), map.height()), (4, 3));
+        for y in 0..3 {
+            for x in 0..4 {
+                assert_eq!(map.at((x, y)), Some(&(x, y)));
+            }
+        }
+        // Off the right edge must not wrap around onto the next row.
+        assert_eq!(map.at((4, 0)), None);
+        assert_eq!(map.at((0, 3)), None);
+        assert_eq!(map.at((usize::MAX, usize::MAX)), None);
+    }
 
-impl<'a, T> Iterator for CellIter<'a, T> {
-    type Item = (BoundCoord, &'a T);
+    #[test]
+    fn test_at_mut() {
+        let mut map = Map::new(3, 2, 0u8);
+        *map.at_mut((2, 1)).unwrap() = 7;
+        *map.at_mut((1, 0)).unwrap() = 3;
+        assert!(map.at_mut((3, 0)).is_none());
+        let tiles = map.tiles().map(|(_, t)| *t).collect::<Vec<_>>();
+        assert_eq!(tiles, [0, 3, 0, 0, 0, 7]);
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.2.next().map(|(i, v)| {
-            (
-                BoundCoord {
-                    width: self.0.width(),
-                    height: self.0.height(),
-                    x: i,
-                    y: self.1,
-                },
-                v,
-            )
-        })
+    #[test]
+    fn test_tiles_and_rows() {
+        let map = coord_map();
+        for (coord, tile) in map.tiles() {
+            assert_eq!((coord.x, coord.y), *tile);
+            assert_eq!((coord.width, coord.height), (4, 3));
+        }
+        assert_eq!(map.tiles().count(), 12);
+
+        let rows = map.rows().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 3);
+        for row in rows {
+            let tiles = row.tiles().map(|(_, t)| *t).collect::<Vec<_>>();
+            assert_eq!(tiles, (0..4).map(|x| (x, row.y())).collect::<Vec<_>>());
+        }
+
+        let empty = Map::<u8>::from_rows(Vec::new());
+        assert_eq!(empty.tiles().count(), 0);
+        assert_eq!(empty.rows().count(), 0);
+    }
+
+    #[test]
+    fn test_bind() {
+        let map = coord_map();
+        let bound = FreeCoord { x: 3, y: 2 }.bind(&map).unwrap();
+        assert_eq!(map.at(&bound), Some(&(3, 2)));
+        assert_eq!(bound.unbind(), FreeCoord { x: 3, y: 2 });
+        assert!(FreeCoord { x: 4, y: 0 }.bind(&map).is_none());
+        assert!(FreeCoord { x: 0, y: 3 }.bind(&map).is_none());
+        assert!(FreeCoord { x: -1, y: 0 }.bind(&map).is_none());
+    }
+
+    #[test]
+    fn test_adj() {
+        let map = coord_map();
+        let adj = |x: i64, y: i64, diags: bool| {
+            let coord = FreeCoord { x, y }.bind(&map).unwrap();
+            let mut adj = if diags {
+                coord.adj_diags().map(|c| (c.x, c.y)).collect::<Vec<_>>()
+            } else {
+                coord.adj_cardinal().map(|c| (c.x, c.y)).collect::<Vec<_>>()
+            };
+            adj.sort();
+            adj
+        };
+        assert_eq!(adj(0, 0, false), [(0, 1), (1, 0)]);
+        assert_eq!(adj(3, 1, false), [(2, 1), (3, 0), (3, 2)]);
+        assert_eq!(adj(1, 1, false), [(0, 1), (1, 0), (1, 2), (2, 1)]);
+        assert_eq!(adj(3, 2, true), [(2, 1), (2, 2), (3, 1)]);
+        assert_eq!(adj(1, 1, true).len(), 8);
+    }
+
+    /// A 3 × 2 map:
+    ///
+    /// ```text
+    /// abc
+    /// def
+    /// ```
+    fn letters() -> Map<char> {
+        Map::from_rows(vec!["abc".chars().collect(), "def".chars().collect()])
+    }
+
+    fn rows_of(map: &Map<char>) -> Vec<String> {
+        map.rows().map(|r| r.tiles().map(|(_, c)| *c).collect()).collect()
+    }
+
+    #[test]
+    fn test_rotate_flip_transpose() {
+        let map = letters();
+        assert_eq!(rows_of(&map.transpose()), ["ad", "be", "cf"]);
+        assert_eq!(rows_of(&map.rotate_cw()), ["da", "eb", "fc"]);
+        assert_eq!(rows_of(&map.rotate_ccw()), ["cf", "be", "ad"]);
+        assert_eq!(rows_of(&map.rotate_180()), ["fed", "cba"]);
+        assert_eq!(rows_of(&map.flip_h()), ["cba", "fed"]);
+        assert_eq!(rows_of(&map.flip_v()), ["def", "abc"]);
+        assert_eq!(map.rotate_cw().rotate_cw(), map.rotate_180());
+        assert_eq!(map.rotate_cw().rotate_ccw(), map);
+        assert_eq!(map.transpose(), map.rotate_cw().flip_h());
+    }
+
+    #[test]
+    fn test_orientations() {
+        let map = letters();
+        let all = map.orientations().collect::<Vec<_>>();
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[0], map);
+        assert_eq!(all[1], map.rotate_cw());
+        assert_eq!(all[4], map.flip_h());
+        for (i, a) in all.iter().enumerate() {
+            for b in all[i + 1..].iter() {
+                assert_ne!(a, b);
+            }
+        }
+        assert!(all.contains(&map.transpose()));
+        assert!(all.contains(&map.flip_v()));
+    }
+
+    #[test]
+    fn test_tile() {
+        // As in 2021 day 15, part B: each copy is one more, wrapping 9 around to 1.
+        let map = Map::from_rows(vec![vec![8u8, 9, 1]]);
+        let tiled = map.tile(2, 3, |&t, tx, ty| ((usize::from(t) - 1 + tx + ty) % 9 + 1) as u8);
+        assert_eq!((tiled.width(), tiled.height()), (6, 3));
+        let expected = [[8, 9, 1, 9, 1, 2], [9, 1, 2, 1, 2, 3], [1, 2, 3, 2, 3, 4]];
+        assert_eq!(tiled, Map::from_rows(expected.iter().map(|r| r.to_vec()).collect()));
+    }
+
+    #[test]
+    fn test_crop_and_pad() {
+        let map = letters();
+        let rect = Rect { x: 1, y: 0, width: 2, height: 2 };
+        assert_eq!(rows_of(&map.crop(rect).unwrap()), ["bc", "ef"]);
+        assert_eq!(map.crop(Rect { x: 2, y: 1, width: 2, height: 1 }), None);
+        assert_eq!(map.crop(Rect { x: 0, y: 0, width: 3, height: 2 }).unwrap(), map);
+
+        let padded = map.pad(1, '.');
+        assert_eq!(rows_of(&padded), [".....", ".abc.", ".def.", "....."]);
+        assert_eq!(padded.crop(Rect { x: 1, y: 1, width: 3, height: 2 }).unwrap(), map);
+        assert_eq!(map.pad(0, '.'), map);
+    }
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    enum Burrow {
+        Wall,
+        Open,
+        Amphipod(char),
+        Outside,
+    }
+
+    #[test]
+    fn test_map_parser() {
+        // 2021 day 23's burrow: ragged, with spaces outside the walls, and a class of letters.
+        let burrow = "#############\n#.S.........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########\n";
+        let parser = MapParser::new()
+            .tile('#', Burrow::Wall)
+            .tile('.', Burrow::Open)
+            .tile(' ', Burrow::Outside)
+            .tiles("ABCD", Burrow::Amphipod)
+            .marker('S', Burrow::Open);
+        assert!(matches!(
+            parser.parse_str(burrow),
+            Err(MapParseError::Ragged { line: 4, width: 11, expected: 13 }),
+        ));
+
+        let parsed = parser.pad(Burrow::Outside).parse_str(burrow).unwrap();
+        let map = &parsed.map;
+        assert_eq!((map.width(), map.height()), (13, 5));
+        assert_eq!(map.at((3, 2)), Some(&Burrow::Amphipod('B')));
+        assert_eq!(map.at((12, 4)), Some(&Burrow::Outside));
+        assert_eq!(map.at((2, 1)), Some(&Burrow::Open));
+        assert_eq!(parsed.marker('S').map(|c| (c.x, c.y)), Some((2, 1)));
+        assert_eq!(parsed.markers('E'), []);
+
+        let err = MapParser::new().tile('.', 0).parse_str("..\n.x\n").unwrap_err();
+        assert!(matches!(err, MapParseError::UnknownChar { line: 2, column: 2, ch: 'x' }));
+        assert_eq!(err.to_string(), "line 2, column 2: 'x' isn't a tile");
+    }
+
+    #[test]
+    fn test_render() {
+        let render = |map: &Map<char>, highlights: &[Highlight<'_>]| {
+            let mut out = Vec::new();
+            map.render_highlighted(&mut out, highlights, Output::Always).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let map = letters();
+        assert_eq!(render(&map, &[]), "abc\ndef\n");
+
+        // A path, with its end picked out on top.
+        let path = Highlight::new(vec![(0, 0), (1, 0), (1, 1)], TermAttr::color(TermColor::Red));
+        let end = Highlight::new(Some((1, 1)), TermAttr::color(TermColor::Green)).with_glyph("E");
+        assert_eq!(
+            render(&map, &[path, end]),
+            "\x1b[31ma\x1b[0m\x1b[31mb\x1b[0mc\nd\x1b[32mE\x1b[0mf\n",
+        );
+
+        let mut out = Vec::new();
+        let digits = Map::from_rows(vec![vec![0u8, 9, 10]]);
+        digits.render(&mut out, Output::Always).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1b[34m0\x1b[0m\x1b[31m9\x1b[0m\x1b[1;91m+\x1b[0m\n",
+        );
+
+        // Nothing styled, highlights included, when the output isn't to be.
+        let mut out = Vec::new();
+        digits.render(&mut out, Output::Never).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "09+\n");
+        let mut out = Vec::new();
+        let path = Highlight::new(vec![(0, 0), (1, 0)], TermAttr::color(TermColor::Red));
+        map.render_highlighted(&mut out, &[path], Output::Never).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "abc\ndef\n");
+    }
+
+    /// Build a `width` × `height` map, and the `Vec<Vec<T>>` it ought to behave like.
+    fn map_and_reference(width: u8, height: u8, seed: Vec<u16>) -> (Map<u16>, Vec<Vec<u16>>) {
+        let (width, height) = (usize::from(width % 9), usize::from(height % 9));
+        let mut seed = seed.into_iter().cycle();
+        let reference = (0..height)
+            .map(|_| (0..width).map(|_| seed.next().unwrap_or(0)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        (Map::from_rows(reference.clone()), reference)
+    }
+
+    quickcheck! {
+        fn prop_at_matches_reference(width: u8, height: u8, seed: Vec<u16>, x: usize, y: usize) -> bool {
+            let (map, reference) = map_and_reference(width, height, seed);
+            let x = x % 10;
+            let y = y % 10;
+            map.at((x, y)) == reference.get(y).and_then(|row| row.get(x))
+        }
+
+        fn prop_at_mut_only_touches_one_tile(width: u8, height: u8, seed: Vec<u16>, x: usize, y: usize) -> bool {
+            let (mut map, mut reference) = map_and_reference(width, height, seed);
+            let x = x % 10;
+            let y = y % 10;
+            if let Some(tile) = map.at_mut((x, y)) {
+                *tile = tile.wrapping_add(1);
+            }
+            if let Some(tile) = reference.get_mut(y).and_then(|row| row.get_mut(x)) {
+                *tile = tile.wrapping_add(1);
+            }
+            let matches = map.tiles().all(|(c, t)| reference[c.y][c.x] == *t);
+            matches
+        }
+
+        fn prop_tiles_are_row_major(width: u8, height: u8, seed: Vec<u16>) -> bool {
+            let (map, reference) = map_and_reference(width, height, seed);
+            let from_tiles = map.tiles().map(|(c, t)| (c.x, c.y, *t)).collect::<Vec<_>>();
+            let from_rows = map
+                .rows()
+                .flat_map(|row| row.tiles().map(|(c, t)| (c.x, c.y, *t)).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let expected = reference
+                .iter()
+                .enumerate()
+                .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, t)| (x, y, *t)))
+                .collect::<Vec<_>>();
+            from_tiles == expected && from_rows == expected
+        }
+
+        fn prop_bind_and_adj_stay_on_map(width: u8, height: u8, x: i8, y: i8) -> bool {
+            let (map, _) = map_and_reference(width, height, vec![0]);
+            let free = FreeCoord { x: i64::from(x % 12), y: i64::from(y % 12) };
+            let on_map = 0 <= free.x
+                && 0 <= free.y
+                && (free.x as usize) < map.width()
+                && (free.y as usize) < map.height();
+            match free.bind(&map) {
+                None => !on_map,
+                Some(bound) => {
+                    let near = |c: &BoundCoord| {
+                        let f = c.unbind();
+                        map.at(c).is_some() && (f.x - free.x).abs() <= 1 && (f.y - free.y).abs() <= 1 && f != free
+                    };
+                    let cardinals = bound.adj_cardinal().collect::<Vec<_>>();
+                    let diags = bound.adj_diags().collect::<Vec<_>>();
+                    on_map
+                        && bound.unbind() == free
+                        && cardinals.iter().all(|c| near(c) && (c.x == bound.x || c.y == bound.y))
+                        && diags.iter().all(near)
+                        && cardinals.iter().all(|c| diags.contains(c))
+                }
+            }
+        }
     }
 }
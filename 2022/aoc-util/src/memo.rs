@@ -0,0 +1,186 @@
+//! Memoizing recursive functions, for the puzzles that are a recursion away from being fast:
+//! 2020 day 10's adapter arrangements, 2021 day 14's polymer counts, 2021 day 21's Dirac dice.
+//!
+//! Write the function to take a `&mut Memo` and wrap its body in [`Memo::get`], passing the memo
+//! along to the recursive calls:
+//!
+//! ```
+//! use aoc_util::memo::Memo;
+//!
+//! fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+//!     memo.get(n, |memo| if n < 2 { n } else { fib(memo, n - 1) + fib(memo, n - 2) })
+//! }
+//!
+//! let mut memo = Memo::new();
+//! assert_eq!(fib(&mut memo, 90), 2880067194370816120);
+//! assert_eq!(memo.stats().misses, 91);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// A cache of a function's results, by argument.
+#[derive(Clone, Debug)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    hits: u64,
+    misses: u64,
+}
+
+/// How well a [`Memo`] has been doing.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoStats {
+    /// Calls answered from the cache.
+    pub hits: u64,
+    /// Calls that had to compute their result.
+    pub misses: u64,
+    /// Results in the cache.
+    pub entries: usize,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Memo<K, V> {
+        Memo {
+            cache: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The result for `key`: from the cache if it's there, otherwise from `compute`, which gets
+    /// the memo back to make its own (recursive) calls with.
+    ///
+    /// The result is cached only once `compute` returns, so a recursion that loops back on itself
+    /// recurses forever, as it would without the memo.
+    pub fn get<F: FnOnce(&mut Memo<K, V>) -> V>(&mut self, key: K, compute: F) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+        self.misses += 1;
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    /// The cached result for `key`, if there is one. Doesn't count towards the stats.
+    pub fn cached(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.cache.len(),
+        }
+    }
+
+    /// Empty the cache, and reset the stats.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Memo<K, V> {
+        Memo::new()
+    }
+}
+
+impl fmt::Display for MemoStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let calls = self.hits + self.misses;
+        let rate = if calls == 0 { 0. } else { self.hits as f64 / calls as f64 * 100. };
+        write!(
+            f,
+            "{} calls, {} hits ({:.1}%), {} entries",
+            calls, self.hits, rate, self.entries
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    /// 2020 day 10: the ways to chain the adapters from `i` on to the end, each step up by 1-3.
+    fn arrangements(memo: &mut Memo<usize, u64>, adapters: &[u64], i: usize) -> u64 {
+        memo.get(i, |memo| {
+            if i == adapters.len() - 1 {
+                return 1;
+            }
+            (i + 1..adapters.len())
+                .take_while(|&j| adapters[j] - adapters[i] <= 3)
+                .map(|j| arrangements(memo, adapters, j))
+                .sum()
+        })
+    }
+
+    fn count_arrangements(adapters: &[u64]) -> u64 {
+        let mut chain = vec![0];
+        chain.extend_from_slice(adapters);
+        chain.sort_unstable();
+        chain.push(chain.last().unwrap() + 3);
+        arrangements(&mut Memo::new(), &chain, 0)
+    }
+
+    #[test]
+    fn test_adapters() {
+        assert_eq!(count_arrangements(&[16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4]), 8);
+        let adapters = [
+            28, 33, 18, 42, 31, 14, 46, 20, 48, 47, 24, 23, 49, 45, 19, 38, 39, 11, 1, 32, 25, 35,
+            8, 17, 7, 9, 4, 2, 34, 10, 3,
+        ];
+        assert_eq!(count_arrangements(&adapters), 19208);
+    }
+
+    /// 2021 day 21's Dirac dice: the universes in which each player wins, from player 1's turn.
+    fn dirac(memo: &mut Memo<[u64; 4], (u64, u64)>, state: [u64; 4]) -> (u64, u64) {
+        let [pos1, score1, pos2, score2] = state;
+        memo.get(state, |memo| {
+            let mut wins = (0, 0);
+            for (roll, universes) in [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)] {
+                let pos = (pos1 + roll - 1) % 10 + 1;
+                let score = score1 + pos;
+                if 21 <= score {
+                    wins.0 += universes;
+                } else {
+                    // Swap the players around, so it's the other one's turn.
+                    let (theirs, ours) = dirac(memo, [pos2, score2, pos, score]);
+                    wins.0 += universes * ours;
+                    wins.1 += universes * theirs;
+                }
+            }
+            wins
+        })
+    }
+
+    #[test]
+    fn test_dirac_dice() {
+        let mut memo = Memo::new();
+        assert_eq!(dirac(&mut memo, [4, 0, 8, 0]), (444356092776315, 341960390180808));
+        let stats = memo.stats();
+        assert_eq!(stats.misses, u64::try_from(stats.entries).unwrap());
+        assert!(stats.misses < stats.hits);
+        assert_eq!(memo.cached(&[4, 0, 8, 0]), Some(&(444356092776315, 341960390180808)));
+
+        memo.clear();
+        assert_eq!(memo.stats(), MemoStats::default());
+    }
+
+    #[test]
+    fn test_stats_display() {
+        let stats = MemoStats {
+            hits: 3,
+            misses: 1,
+            entries: 1,
+        };
+        assert_eq!(stats.to_string(), "4 calls, 3 hits (75.0%), 1 entries");
+    }
+}
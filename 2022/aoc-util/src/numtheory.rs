@@ -0,0 +1,200 @@
+//! Modular arithmetic: for when a puzzle's answer is "the first time everything lines up" (2020
+//! day 13's buses) or it's quietly doing Diffie-Hellman (2020 day 25's door and card).
+//!
+//! Moduli are positive `i64`s; results are always in `0..m`. Products are taken in `i128`, so
+//! nothing overflows for any modulus that fits in an `i64`.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// The greatest common divisor, which is never negative. `gcd(0, 0) == 0`.
+pub fn gcd(a: i64, b: i64) -> i64 {
+    egcd(a, b).0
+}
+
+/// The extended Euclidean algorithm: `(g, x, y)`, where `g` is `gcd(a, b)` and `a*x + b*y == g`.
+pub fn egcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The least common multiple, which is never negative. Panics if it doesn't fit in an `i64`.
+pub fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (a / gcd(a, b)).checked_mul(b).unwrap().abs()
+}
+
+/// `a` reduced into `0..m`.
+pub fn modulo(a: i64, m: i64) -> i64 {
+    assert!(0 < m, "modulus must be positive, not {}", m);
+    a.rem_euclid(m)
+}
+
+/// `a * b`, mod `m`.
+pub fn mod_mul(a: i64, b: i64, m: i64) -> i64 {
+    assert!(0 < m, "modulus must be positive, not {}", m);
+    let product = (i128::from(a) * i128::from(b)).rem_euclid(i128::from(m));
+    i64::try_from(product).unwrap()
+}
+
+/// `base` to the power `exp`, mod `m`, by repeated squaring.
+pub fn modpow(base: i64, mut exp: u64, m: i64) -> i64 {
+    let mut result = modulo(1, m);
+    let mut base = modulo(base, m);
+    while 0 < exp {
+        if exp & 1 == 1 {
+            result = mod_mul(result, base, m);
+        }
+        base = mod_mul(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// The `x` in `0..m` with `a * x ≡ 1 (mod m)`, or `None` if `a` and `m` share a factor.
+pub fn modinv(a: i64, m: i64) -> Option<i64> {
+    let (g, x, _) = egcd(modulo(a, m), m);
+    if g == 1 {
+        Some(modulo(x, m))
+    } else {
+        None
+    }
+}
+
+/// The Chinese remainder theorem: solve `x ≡ r (mod m)` for every `(r, m)` in `congruences`.
+///
+/// Returns `(x, l)`, where `l` is the lcm of the moduli and `x` is in `0..l`: every solution is
+/// `x` plus a multiple of `l`. The moduli needn't be coprime, so the congruences can contradict
+/// each other, as `x ≡ 0 (mod 2)` and `x ≡ 1 (mod 4)` do; then there's no solution, and `None`.
+/// Panics if `l` doesn't fit in an `i64`.
+pub fn crt<I: IntoIterator<Item = (i64, i64)>>(congruences: I) -> Option<(i64, i64)> {
+    let mut x = 0;
+    let mut l = 1;
+    for (r, m) in congruences {
+        let r = modulo(r, m);
+        // Want k with x + l*k ≡ r (mod m): l*k ≡ r - x, which is solvable only if gcd(l, m)
+        // divides r - x.
+        let (g, inv, _) = egcd(l, m);
+        let diff = r - x;
+        if diff % g != 0 {
+            return None;
+        }
+        let step = m / g;
+        let k = mod_mul(diff / g, inv, step);
+        let new_l = lcm(l, m);
+        x = modulo(x + mod_mul(l, k, new_l), new_l);
+        l = new_l;
+    }
+    Some((x, l))
+}
+
+/// The discrete logarithm: the smallest `e` with `base^e ≡ target (mod m)`, or `None` if there
+/// isn't one. Baby-step giant-step, so it takes about `sqrt(m)` time and space.
+///
+/// `base` must be coprime to `m` (as it is when `m` is prime, like 2020 day 25's 20201227).
+pub fn discrete_log(base: i64, target: i64, m: i64) -> Option<u64> {
+    let base = modulo(base, m);
+    let target = modulo(target, m);
+    let inverse = modinv(base, m).expect("base must be coprime to the modulus");
+    let n = (m as f64).sqrt().ceil() as i64 + 1;
+
+    // Baby steps: target * base^-j for j in 0..n, remembering the smallest j for each.
+    let mut baby = HashMap::new();
+    let mut value = target;
+    for j in 0..n {
+        baby.entry(value).or_insert(j);
+        value = mod_mul(value, inverse, m);
+    }
+    // Giant steps: base^(i*n), looking for one that matches a baby step.
+    let giant = modpow(base, u64::try_from(n).unwrap(), m);
+    let mut value = modulo(1, m);
+    for i in 0..n {
+        if let Some(j) = baby.get(&value) {
+            return Some(u64::try_from(i * n + j).unwrap());
+        }
+        value = mod_mul(value, giant, m);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(egcd(240, 46), (2, -9, 47));
+        assert_eq!(egcd(-240, 46).0, 2);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(gcd(0, -7), 7);
+        assert_eq!(lcm(4, -6), 12);
+        assert_eq!(lcm(0, 6), 0);
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(modulo(-7, 5), 3);
+        assert_eq!(modinv(3, 11), Some(4));
+        assert_eq!(modinv(-3, 11), Some(7));
+        assert_eq!(modinv(6, 9), None);
+        assert_eq!(modpow(2, 10, 1000), 24);
+        assert_eq!(modpow(5, 0, 1), 0);
+        // Big enough that a plain i64 product would overflow.
+        let m = 1_000_000_000_000_000_003;
+        assert_eq!(mod_mul(m - 1, m - 1, m), 1);
+        assert_eq!(modpow(m - 1, u64::MAX, m), m - 1);
+    }
+
+    /// 2020 day 13's part 2: the first `t` at which each bus leaves its index minutes after `t`.
+    fn buses(schedule: &str) -> i64 {
+        let congruences = schedule
+            .split(',')
+            .enumerate()
+            .filter(|(_, bus)| *bus != "x")
+            .map(|(i, bus)| (-(i as i64), bus.parse().unwrap()));
+        crt(congruences).unwrap().0
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(buses("7,13,x,x,59,x,31,19"), 1068781);
+        assert_eq!(buses("17,x,13,19"), 3417);
+        assert_eq!(buses("67,7,59,61"), 754018);
+        assert_eq!(buses("1789,37,47,1889"), 1202161486);
+
+        // Moduli that share factors.
+        assert_eq!(crt(vec![(2, 6), (8, 9)]), Some((8, 18)));
+        assert_eq!(crt(vec![(0, 2), (1, 4)]), None);
+        assert_eq!(crt(vec![(3, 4), (3, 4)]), Some((3, 4)));
+        assert_eq!(crt(Vec::new()), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        // 2020 day 25's example: the card's and door's loop sizes, then the encryption key.
+        let m = 20201227;
+        let card = discrete_log(7, 5764801, m).unwrap();
+        let door = discrete_log(7, 17807724, m).unwrap();
+        assert_eq!((card, door), (8, 11));
+        assert_eq!(modpow(17807724, card, m), 14897079);
+        assert_eq!(modpow(5764801, door, m), 14897079);
+
+        assert_eq!(discrete_log(3, 1, 7), Some(0));
+        // 2 only generates {1, 2, 4} mod 7.
+        assert_eq!(discrete_log(2, 3, 7), None);
+    }
+}
@@ -0,0 +1,205 @@
+//! Reading the block letters some puzzles draw as their answer (2016 day 8, 2019 days 8 and 11,
+//! 2021 day 13, 2022 day 10 in the small font; 2018 day 10 in the large one).
+//!
+//! The small font is 6 dots tall, and most letters are 4 wide; the large one is 10 tall, and 6
+//! wide. Letters are split at the columns with no dots in them.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use once_cell::sync::Lazy;
+
+use crate::map::Map;
+
+const SMALL: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const LARGE: &[(char, &str)] = &[
+    ('A', "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#"),
+    ('B', "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####."),
+    ('C', ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####."),
+    ('E', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('F', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('G', ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#"),
+    ('H', "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#"),
+    ('J', "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n#...#.\n.###.."),
+    ('K', "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#"),
+    ('L', "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('N', "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#"),
+    ('P', "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('R', "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#"),
+    ('X', "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#"),
+    ('Z', "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######"),
+];
+
+/// Each font's letters, keyed by their drawing with the empty columns at the edges trimmed off.
+static GLYPHS: Lazy<HashMap<String, char>> = Lazy::new(|| {
+    SMALL
+        .iter()
+        .chain(LARGE.iter())
+        .map(|(letter, drawing)| {
+            let rows = drawing.lines().map(|l| l.chars().map(|c| c == '#').collect());
+            let map = Map::from_rows(rows.collect());
+            let glyph = split_glyphs(&map).pop().unwrap();
+            (glyph, *letter)
+        })
+        .collect()
+});
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum OcrError {
+    #[error("letters are 6 or 10 dots tall, but these are {0}")]
+    BadHeight(usize),
+    #[error("letter {position} isn't one we know:\n{glyph}")]
+    UnknownGlyph { position: usize, glyph: String },
+}
+
+/// Read the letters drawn with the `true` tiles of `map`. Empty rows and columns around the
+/// letters are ignored.
+pub fn recognize(map: &Map<bool>) -> Result<String, OcrError> {
+    let rows = (0..map.height())
+        .filter(|&y| (0..map.width()).any(|x| *map.at((x, y)).unwrap()))
+        .collect::<Vec<_>>();
+    let (top, bottom) = match (rows.first(), rows.last()) {
+        (Some(&top), Some(&bottom)) => (top, bottom),
+        _ => return Ok(String::new()),
+    };
+    let height = bottom - top + 1;
+    if height != 6 && height != 10 {
+        return Err(OcrError::BadHeight(height));
+    }
+    let trimmed = Map::from_fn(map.width(), height, |x, y| *map.at((x, y + top)).unwrap());
+
+    split_glyphs(&trimmed)
+        .into_iter()
+        .enumerate()
+        .map(|(position, glyph)| {
+            GLYPHS.get(&glyph).copied().ok_or(OcrError::UnknownGlyph { position, glyph })
+        })
+        .collect()
+}
+
+/// Read the letters drawn by a set of dots, as `(x, y)`.
+pub fn recognize_dots<I: IntoIterator<Item = (i64, i64)>>(dots: I) -> Result<String, OcrError> {
+    let dots = dots.into_iter().collect::<Vec<_>>();
+    if dots.is_empty() {
+        return Ok(String::new());
+    }
+    let min_x = dots.iter().map(|d| d.0).min().unwrap();
+    let min_y = dots.iter().map(|d| d.1).min().unwrap();
+    let width = dots.iter().map(|d| d.0 - min_x + 1).max().unwrap();
+    let height = dots.iter().map(|d| d.1 - min_y + 1).max().unwrap();
+    let to_usize = |n: i64| usize::try_from(n).unwrap();
+    let mut map = Map::new(to_usize(width), to_usize(height), false);
+    for (x, y) in dots {
+        *map.at_mut((to_usize(x - min_x), to_usize(y - min_y))).unwrap() = true;
+    }
+    recognize(&map)
+}
+
+/// Split the map into runs of columns with dots in them, each drawn with `#` and `.`, a line per
+/// row.
+fn split_glyphs(map: &Map<bool>) -> Vec<String> {
+    let column_empty = |x: usize| (0..map.height()).all(|y| !*map.at((x, y)).unwrap());
+    let mut glyphs = Vec::new();
+    let mut x = 0;
+    while x < map.width() {
+        if column_empty(x) {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x < map.width() && !column_empty(x) {
+            x += 1;
+        }
+        let glyph = (0..map.height())
+            .map(|y| {
+                let dot = |x| if *map.at((x, y)).unwrap() { '#' } else { '.' };
+                (start..x).map(dot).collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        glyphs.push(glyph);
+    }
+    glyphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draw `word` in a font, a column of space between letters, with a margin around it all.
+    fn draw(font: &[(char, &str)], word: &str) -> Map<bool> {
+        let letters = word
+            .chars()
+            .map(|c| font.iter().find(|(l, _)| *l == c).unwrap().1.lines().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let height = letters[0].len();
+        let rows = (0..height)
+            .map(|y| {
+                let line = letters.iter().map(|l| l[y]).collect::<Vec<_>>().join(".");
+                format!("..{}..", line).chars().map(|c| c == '#').collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let blank = vec![false; rows[0].len()];
+        let mut padded = vec![blank.clone()];
+        padded.extend(rows);
+        padded.push(blank);
+        Map::from_rows(padded)
+    }
+
+    #[test]
+    fn test_every_letter() {
+        let small = SMALL.iter().map(|(l, _)| l).collect::<String>();
+        assert_eq!(recognize(&draw(SMALL, &small)).unwrap(), small);
+        let large = LARGE.iter().map(|(l, _)| l).collect::<String>();
+        assert_eq!(recognize(&draw(LARGE, &large)).unwrap(), large);
+    }
+
+    #[test]
+    fn test_dots() {
+        // "HI", shifted and with the dots out of order, as a fold might leave them.
+        let drawing = ["#..#.###", "#..#..#.", "####..#.", "#..#..#.", "#..#..#.", "#..#.###"];
+        let mut dots = Vec::new();
+        for (y, row) in drawing.iter().enumerate().rev() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    dots.push((i64::try_from(x).unwrap() - 7, i64::try_from(y).unwrap() + 3));
+                }
+            }
+        }
+        assert_eq!(recognize_dots(dots).unwrap(), "HI");
+        assert_eq!(recognize_dots(Vec::new()).unwrap(), "");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(recognize_dots(vec![(0, 0), (0, 1)]), Err(OcrError::BadHeight(2)));
+        let mut map = draw(SMALL, "AB");
+        *map.at_mut((3, 2)).unwrap() = true;
+        match recognize(&map) {
+            Err(OcrError::UnknownGlyph { position: 0, glyph }) => {
+                assert!(glyph.starts_with(".##.\n##.#"))
+            }
+            other => panic!("expected an unknown glyph, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Sparse voxel trees: a [`Quadtree`] in 2D, an [`Octree`] in 3D.
+//!
+//! The space is a cube whose side is a power of two; each node is either a single value for its
+//! whole cube, or split into `2^N` half-sized children. Filling a box splits only the nodes along
+//! its faces, so big uniform areas cost little, and looking up a point takes `log2(side)` steps.
+//!
+//! A face at an odd coordinate still needs a leaf for every point on it, though, so boxes
+//! thousands of points wide make for a huge tree: 2021 day 22's part B is better done with a
+//! [`Region`](crate::cuboid::Region), which doesn't care how big its boxes are.
+
+use std::convert::TryFrom;
+
+use crate::cuboid::Cuboid;
+use crate::interval::Interval;
+use crate::point::Point;
+
+/// A tree over `N` dimensions, holding a `T` at each point of its bounds.
+#[derive(Clone, Debug)]
+pub struct Orthtree<T, const N: usize> {
+    /// The points the tree holds values for; everywhere else is the background.
+    bounds: Cuboid<N>,
+    /// The corner of the root's cube, and its side.
+    origin: Point<N>,
+    size: i64,
+    background: T,
+    root: Node<T>,
+}
+
+pub type Quadtree<T> = Orthtree<T, 2>;
+pub type Octree<T> = Orthtree<T, 3>;
+
+#[derive(Clone, Debug)]
+enum Node<T> {
+    Leaf(T),
+    /// `2^N` children; bit `axis` of a child's index is set if it's the upper half on that axis.
+    Split(Vec<Node<T>>),
+}
+
+impl<T: Clone + PartialEq, const N: usize> Orthtree<T, N> {
+    /// A tree over `bounds`, with every point set to `background`.
+    pub fn new(bounds: Cuboid<N>, background: T) -> Orthtree<T, N> {
+        let mut origin = Point::ORIGIN;
+        for (axis, interval) in bounds.axes.iter().enumerate() {
+            origin[axis] = interval.start;
+        }
+        let longest = bounds.axes.iter().map(|i| i.len()).max().unwrap_or(0);
+        let size = u64::try_from(longest.max(1)).unwrap().next_power_of_two();
+        Orthtree {
+            bounds,
+            origin,
+            size: i64::try_from(size).unwrap(),
+            root: Node::Leaf(background.clone()),
+            background,
+        }
+    }
+
+    pub fn bounds(&self) -> &Cuboid<N> {
+        &self.bounds
+    }
+
+    /// Set every point in `cuboid` to `value`. Points outside the tree's bounds are ignored.
+    pub fn fill(&mut self, cuboid: &Cuboid<N>, value: T) {
+        if let Some(target) = cuboid.intersection(&self.bounds) {
+            self.root.fill(self.origin, self.size, &target, &value);
+        }
+    }
+
+    /// Set every point in `cuboid` back to the background.
+    pub fn clear(&mut self, cuboid: &Cuboid<N>) {
+        self.fill(cuboid, self.background.clone());
+    }
+
+    /// The value at `point`, or `None` if it's outside the tree's bounds.
+    pub fn get(&self, point: &Point<N>) -> Option<&T> {
+        if !self.bounds.contains(point) {
+            return None;
+        }
+        let mut node = &self.root;
+        let mut origin = self.origin;
+        let mut size = self.size;
+        while let Node::Split(children) = node {
+            size /= 2;
+            let mut index = 0;
+            for axis in 0..N {
+                if origin[axis] + size <= point[axis] {
+                    index |= 1 << axis;
+                    origin[axis] += size;
+                }
+            }
+            node = &children[index];
+        }
+        match node {
+            Node::Leaf(value) => Some(value),
+            Node::Split(_) => unreachable!(),
+        }
+    }
+
+    /// The number of points set to `value`.
+    pub fn count(&self, value: &T) -> u64 {
+        self.count_in(&self.bounds, value)
+    }
+
+    /// The number of points in `cuboid` set to `value`.
+    pub fn count_in(&self, cuboid: &Cuboid<N>, value: &T) -> u64 {
+        self.regions(cuboid)
+            .into_iter()
+            .filter(|(_, v)| *v == value)
+            .map(|(c, _)| c.volume())
+            .sum()
+    }
+
+    /// The tree's values within `cuboid`, as disjoint boxes each holding a single value. Together
+    /// they cover the part of `cuboid` inside the tree's bounds.
+    pub fn regions(&self, cuboid: &Cuboid<N>) -> Vec<(Cuboid<N>, &T)> {
+        let mut regions = Vec::new();
+        if let Some(target) = cuboid.intersection(&self.bounds) {
+            self.root.regions(self.origin, self.size, &target, &mut regions);
+        }
+        regions
+    }
+
+    /// The number of nodes in the tree, to see how well it's compressing.
+    pub fn node_count(&self) -> usize {
+        self.root.node_count()
+    }
+}
+
+impl<const N: usize> Orthtree<bool, N> {
+    /// The number of points that are set.
+    pub fn volume(&self) -> u64 {
+        self.count(&true)
+    }
+}
+
+impl<T: Clone + PartialEq> Node<T> {
+    fn fill<const N: usize>(&mut self, origin: Point<N>, size: i64, target: &Cuboid<N>, value: &T) {
+        let cell = cell(origin, size);
+        if target.contains_cuboid(&cell) {
+            *self = Node::Leaf(value.clone());
+            return;
+        }
+        if cell.intersection(target).is_none() {
+            return;
+        }
+        // Only partly covered, so the cell is bigger than a point, and can be split.
+        if let Node::Leaf(current) = self {
+            if current == value {
+                return;
+            }
+            *self = Node::Split(vec![Node::Leaf(current.clone()); 1 << N]);
+        }
+        if let Node::Split(children) = self {
+            let half = size / 2;
+            for (index, child) in children.iter_mut().enumerate() {
+                child.fill(child_origin(origin, half, index), half, target, value);
+            }
+            // Merge the children back together if they've ended up all the same.
+            if let Node::Leaf(first) = &children[0] {
+                let same = |c: &Node<T>| matches!(c, Node::Leaf(v) if v == first);
+                if children.iter().all(same) {
+                    *self = Node::Leaf(first.clone());
+                }
+            }
+        }
+    }
+
+    fn regions<'a, const N: usize>(
+        &'a self,
+        origin: Point<N>,
+        size: i64,
+        target: &Cuboid<N>,
+        regions: &mut Vec<(Cuboid<N>, &'a T)>,
+    ) {
+        let overlap = match cell(origin, size).intersection(target) {
+            Some(overlap) => overlap,
+            None => return,
+        };
+        match self {
+            Node::Leaf(value) => regions.push((overlap, value)),
+            Node::Split(children) => {
+                let half = size / 2;
+                for (index, child) in children.iter().enumerate() {
+                    child.regions(child_origin(origin, half, index), half, target, regions);
+                }
+            }
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        match self {
+            Node::Leaf(_) => 1,
+            Node::Split(children) => 1 + children.iter().map(|c| c.node_count()).sum::<usize>(),
+        }
+    }
+}
+
+/// The cube with corner `origin` and side `size`.
+fn cell<const N: usize>(origin: Point<N>, size: i64) -> Cuboid<N> {
+    let mut axes = [Interval::new(0, 0); N];
+    for (axis, interval) in axes.iter_mut().enumerate() {
+        *interval = Interval::new(origin[axis], origin[axis] + size);
+    }
+    Cuboid::new(axes)
+}
+
+fn child_origin<const N: usize>(mut origin: Point<N>, half: i64, index: usize) -> Point<N> {
+    for axis in 0..N {
+        if index & (1 << axis) != 0 {
+            origin[axis] += half;
+        }
+    }
+    origin
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cuboid::Region;
+    use quickcheck::quickcheck;
+
+    fn cube(lo: [i64; 3], hi: [i64; 3]) -> Cuboid<3> {
+        Cuboid::inclusive(Point(lo), Point(hi))
+    }
+
+    #[test]
+    fn test_reactor_reboot() {
+        // 2021 day 22's first example.
+        let mut reactor = Octree::new(cube([-50, -50, -50], [50, 50, 50]), false);
+        reactor.fill(&cube([10, 10, 10], [12, 12, 12]), true);
+        reactor.fill(&cube([11, 11, 11], [13, 13, 13]), true);
+        assert_eq!(reactor.volume(), 27 + 19);
+        reactor.clear(&cube([9, 9, 9], [11, 11, 11]));
+        reactor.fill(&cube([10, 10, 10], [10, 10, 10]), true);
+        assert_eq!(reactor.volume(), 39);
+
+        assert_eq!(reactor.get(&Point([10, 10, 10])), Some(&true));
+        assert_eq!(reactor.get(&Point([11, 11, 11])), Some(&false));
+        assert_eq!(reactor.get(&Point([51, 0, 0])), None);
+        assert_eq!(reactor.count_in(&cube([12, 12, 12], [50, 50, 50]), &true), 8);
+
+        // Clearing everything collapses the tree back to a single leaf.
+        reactor.clear(&cube([-100, -100, -100], [100, 100, 100]));
+        assert_eq!((reactor.volume(), reactor.node_count()), (0, 1));
+    }
+
+    #[test]
+    fn test_quadtree_values() {
+        let rect = |x0, y0, x1, y1| Cuboid::inclusive(Point([x0, y0]), Point([x1, y1]));
+        let mut tree = Quadtree::new(rect(0, 0, 9, 5), 0u8);
+        tree.fill(&rect(2, 2, 7, 3), 1);
+        tree.fill(&rect(5, 0, 5, 20), 2);
+        assert_eq!(tree.count(&0), 60 - 12 - 4);
+        assert_eq!(tree.count(&1), 10);
+        assert_eq!(tree.count(&2), 6);
+        assert_eq!(tree.get(&Point([5, 2])), Some(&2));
+        assert_eq!(tree.get(&Point([6, 2])), Some(&1));
+
+        let regions = tree.regions(&rect(4, 2, 6, 2));
+        assert_eq!(regions.iter().map(|(c, _)| c.volume()).sum::<u64>(), 3);
+        assert!(regions.iter().all(|(c, v)| (c.axes[0].start == 5) == (**v == 2)));
+    }
+
+    /// Fill or clear a box, given by a corner and its extent.
+    type Op = (bool, (i8, i8, i8), (u8, u8, u8));
+
+    quickcheck! {
+        fn prop_matches_region(ops: Vec<Op>) -> bool {
+            let bounds = cube([-40, -40, -40], [40, 40, 40]);
+            let mut tree = Octree::new(bounds, false);
+            let mut region = Region::new();
+            for (on, (x, y, z), (dx, dy, dz)) in ops.into_iter().take(30) {
+                let lo = [x, y, z].map(|v| i64::from(v / 4));
+                let hi = [(lo[0], dx), (lo[1], dy), (lo[2], dz)];
+                let hi = hi.map(|(v, d)| v + i64::from(d % 32));
+                let c = cube(lo, hi);
+                if on {
+                    tree.fill(&c, true);
+                    region.insert(c);
+                } else {
+                    tree.clear(&c);
+                    region.remove(c);
+                }
+            }
+            tree.volume() == region.clip(&bounds).volume()
+        }
+    }
+}
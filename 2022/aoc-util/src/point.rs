@@ -0,0 +1,372 @@
+//! Points in N dimensions, for puzzles that don't stay on a flat map.
+
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+/// A point (or vector) in `N` dimensions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Point<const N: usize>(pub [i64; N]);
+
+impl<const N: usize> Point<N> {
+    pub const ORIGIN: Point<N> = Point([0; N]);
+
+    pub fn new(coords: [i64; N]) -> Point<N> {
+        Point(coords)
+    }
+
+    /// Sum of the absolute differences along each axis. (Taxicab distance.)
+    pub fn manhattan(&self, other: &Point<N>) -> i64 {
+        self.0.iter().zip(other.0.iter()).map(|(a, b)| (a - b).abs()).sum()
+    }
+
+    /// The largest absolute difference along any axis. (King's-move distance.)
+    pub fn chebyshev(&self, other: &Point<N>) -> i64 {
+        self.0.iter().zip(other.0.iter()).map(|(a, b)| (a - b).abs()).max().unwrap_or(0)
+    }
+
+    /// All 3^N − 1 points adjacent to this one, diagonals included.
+    pub fn neighbors(&self) -> impl Iterator<Item = Point<N>> {
+        let center = *self;
+        let count = 3usize.pow(u32::try_from(N).unwrap());
+        let middle = count / 2;
+        (0..count).filter(move |&i| i != middle).map(move |mut i| {
+            let mut p = center;
+            for axis in 0..N {
+                p.0[axis] += (i % 3) as i64 - 1;
+                i /= 3;
+            }
+            p
+        })
+    }
+
+    /// The 2N points adjacent to this one along a single axis.
+    pub fn orthogonal_neighbors(&self) -> impl Iterator<Item = Point<N>> {
+        let center = *self;
+        (0..N).flat_map(move |axis| {
+            [-1, 1].iter().map(move |d| {
+                let mut p = center;
+                p.0[axis] += d;
+                p
+            })
+        })
+    }
+}
+
+impl<const N: usize> Default for Point<N> {
+    fn default() -> Point<N> {
+        Point::ORIGIN
+    }
+}
+
+impl<const N: usize> From<[i64; N]> for Point<N> {
+    fn from(coords: [i64; N]) -> Point<N> {
+        Point(coords)
+    }
+}
+
+impl<const N: usize> Index<usize> for Point<N> {
+    type Output = i64;
+
+    fn index(&self, axis: usize) -> &i64 {
+        &self.0[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Point<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut i64 {
+        &mut self.0[axis]
+    }
+}
+
+impl<const N: usize> Add for Point<N> {
+    type Output = Point<N>;
+
+    fn add(mut self, rhs: Point<N>) -> Point<N> {
+        self += rhs;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for Point<N> {
+    fn add_assign(&mut self, rhs: Point<N>) {
+        for (a, b) in self.0.iter_mut().zip(rhs.0.iter()) {
+            *a = a.checked_add(*b).unwrap();
+        }
+    }
+}
+
+impl<const N: usize> Sub for Point<N> {
+    type Output = Point<N>;
+
+    fn sub(mut self, rhs: Point<N>) -> Point<N> {
+        self -= rhs;
+        self
+    }
+}
+
+impl<const N: usize> SubAssign for Point<N> {
+    fn sub_assign(&mut self, rhs: Point<N>) {
+        for (a, b) in self.0.iter_mut().zip(rhs.0.iter()) {
+            *a = a.checked_sub(*b).unwrap();
+        }
+    }
+}
+
+impl<const N: usize> Neg for Point<N> {
+    type Output = Point<N>;
+
+    fn neg(mut self) -> Point<N> {
+        for a in self.0.iter_mut() {
+            *a = a.checked_neg().unwrap();
+        }
+        self
+    }
+}
+
+impl<const N: usize> Mul<i64> for Point<N> {
+    type Output = Point<N>;
+
+    fn mul(mut self, rhs: i64) -> Point<N> {
+        for a in self.0.iter_mut() {
+            *a = a.checked_mul(rhs).unwrap();
+        }
+        self
+    }
+}
+
+/// An inclusive, axis-aligned bounding box in `N` dimensions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct BoundingBox<const N: usize> {
+    pub min: Point<N>,
+    pub max: Point<N>,
+}
+
+impl<const N: usize> BoundingBox<N> {
+    /// A box holding just `point`.
+    pub fn point(point: Point<N>) -> BoundingBox<N> {
+        BoundingBox {
+            min: point,
+            max: point,
+        }
+    }
+
+    /// The smallest box holding all of `points`, or `None` if there aren't any.
+    pub fn around<I: IntoIterator<Item = Point<N>>>(points: I) -> Option<BoundingBox<N>> {
+        let mut points = points.into_iter();
+        let first = BoundingBox::point(points.next()?);
+        Some(points.fold(first, |b, p| b.grow_to(p)))
+    }
+
+    /// The smallest box holding both this box and `point`.
+    pub fn grow_to(mut self, point: Point<N>) -> BoundingBox<N> {
+        for axis in 0..N {
+            self.min.0[axis] = self.min.0[axis].min(point.0[axis]);
+            self.max.0[axis] = self.max.0[axis].max(point.0[axis]);
+        }
+        self
+    }
+
+    /// This box grown by `n` on every side.
+    pub fn expand(self, n: i64) -> BoundingBox<N> {
+        BoundingBox {
+            min: self.min - Point([n; N]),
+            max: self.max + Point([n; N]),
+        }
+    }
+
+    pub fn contains(&self, point: &Point<N>) -> bool {
+        (0..N).all(|axis| self.min.0[axis] <= point.0[axis] && point.0[axis] <= self.max.0[axis])
+    }
+
+    /// The number of points in the box; 0 if `max` is below `min` on any axis.
+    pub fn volume(&self) -> u64 {
+        (0..N)
+            .map(|axis| u64::try_from(self.max.0[axis] - self.min.0[axis] + 1).unwrap_or(0))
+            .product()
+    }
+
+    /// Every point in the box, with the first axis changing fastest.
+    pub fn points(&self) -> impl Iterator<Item = Point<N>> {
+        let bounds = *self;
+        let non_empty = (0..N).all(|axis| bounds.min.0[axis] <= bounds.max.0[axis]);
+        let mut next = Some(bounds.min).filter(|_| non_empty);
+        std::iter::from_fn(move || {
+            let current = next?;
+            let mut p = current;
+            next = None;
+            for axis in 0..N {
+                if p.0[axis] < bounds.max.0[axis] {
+                    p.0[axis] += 1;
+                    next = Some(p);
+                    break;
+                }
+                p.0[axis] = bounds.min.0[axis];
+            }
+            Some(current)
+        })
+    }
+}
+
+/// A sparse set of points in `N` dimensions: the "active" cells of a Conway-style puzzle.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PointSet<const N: usize> {
+    points: HashSet<Point<N>>,
+}
+
+impl<const N: usize> PointSet<N> {
+    pub fn new() -> PointSet<N> {
+        PointSet {
+            points: HashSet::new(),
+        }
+    }
+
+    /// Add `point`; returns whether it was newly added.
+    pub fn insert(&mut self, point: Point<N>) -> bool {
+        self.points.insert(point)
+    }
+
+    /// Remove `point`; returns whether it was present.
+    pub fn remove(&mut self, point: &Point<N>) -> bool {
+        self.points.remove(point)
+    }
+
+    pub fn contains(&self, point: &Point<N>) -> bool {
+        self.points.contains(point)
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Iterate over the points, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Point<N>> {
+        self.points.iter()
+    }
+
+    /// The smallest box holding every point; `None` if the set is empty.
+    pub fn bounds(&self) -> Option<BoundingBox<N>> {
+        BoundingBox::around(self.points.iter().copied())
+    }
+
+    /// For every point that's adjacent (diagonals included) to at least one point in the set, how
+    /// many points in the set it's adjacent to.
+    ///
+    /// This is the heart of a Conway-style step: a point's next state depends only on whether
+    /// it's in the set, and its count here.
+    pub fn neighbor_counts(&self) -> HashMap<Point<N>, usize> {
+        let mut counts = HashMap::new();
+        for point in self.points.iter() {
+            for neighbor in point.neighbors() {
+                *counts.entry(neighbor).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
+
+impl<const N: usize> FromIterator<Point<N>> for PointSet<N> {
+    fn from_iter<I: IntoIterator<Item = Point<N>>>(iter: I) -> PointSet<N> {
+        PointSet {
+            points: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<const N: usize> Extend<Point<N>> for PointSet<N> {
+    fn extend<I: IntoIterator<Item = Point<N>>>(&mut self, iter: I) {
+        self.points.extend(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic_and_distance() {
+        let a = Point([1, -2, 3]);
+        let b = Point([4, 0, -1]);
+        assert_eq!(a + b, Point([5, -2, 2]));
+        assert_eq!(a - b, Point([-3, -2, 4]));
+        assert_eq!(-a, Point([-1, 2, -3]));
+        assert_eq!(a * 2, Point([2, -4, 6]));
+        assert_eq!(a[2], 3);
+        assert_eq!(a.manhattan(&b), 3 + 2 + 4);
+        assert_eq!(a.chebyshev(&b), 4);
+        assert_eq!(Point::<3>::ORIGIN, Point::default());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        fn check<const N: usize>(expected: usize) {
+            let p = Point([7; N]);
+            let neighbors = p.neighbors().collect::<HashSet<_>>();
+            assert_eq!(neighbors.len(), expected);
+            assert!(neighbors.iter().all(|n| n.chebyshev(&p) == 1));
+            let orthogonal = p.orthogonal_neighbors().collect::<HashSet<_>>();
+            assert_eq!(orthogonal.len(), 2 * N);
+            assert!(orthogonal.iter().all(|n| n.manhattan(&p) == 1 && neighbors.contains(n)));
+        }
+        check::<1>(2);
+        check::<2>(8);
+        check::<3>(26);
+        check::<4>(80);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let points = vec![Point([1, 5]), Point([-2, 3]), Point([0, 4])];
+        let bounds = BoundingBox::around(points).unwrap();
+        assert_eq!(bounds, BoundingBox { min: Point([-2, 3]), max: Point([1, 5]) });
+        assert_eq!(bounds.volume(), 4 * 3);
+        let points = bounds.points().collect::<Vec<_>>();
+        assert_eq!(points.len(), 12);
+        assert_eq!(points[..2], [Point([-2, 3]), Point([-1, 3])]);
+        assert!(points.iter().all(|p| bounds.contains(p)));
+        assert!(!bounds.contains(&Point([2, 4])));
+        assert_eq!(bounds.expand(1).volume(), 6 * 5);
+        assert_eq!(BoundingBox::<2>::around(Vec::new()), None);
+
+        // Inverted on an axis: empty, as far as both `volume` and `points` are concerned.
+        let inverted = BoundingBox { min: Point([0, 3]), max: Point([5, 1]) };
+        assert_eq!(inverted.volume(), 0);
+        assert_eq!(inverted.points().count(), 0);
+        assert_eq!(BoundingBox { min: Point([1, 0]), max: Point([0, 0]) }.volume(), 0);
+    }
+
+    /// Run 2020 day 17's Conway cubes for 6 cycles in `N` dimensions.
+    fn conway_cubes<const N: usize>() -> usize {
+        let mut active = PointSet::<N>::new();
+        for (y, line) in [".#.", "..#", "###"].iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if c == '#' {
+                    let mut p = Point::ORIGIN;
+                    p[0] = x as i64;
+                    p[1] = y as i64;
+                    active.insert(p);
+                }
+            }
+        }
+        for _ in 0..6 {
+            active = active
+                .neighbor_counts()
+                .into_iter()
+                .filter(|(p, n)| *n == 3 || (*n == 2 && active.contains(p)))
+                .map(|(p, _)| p)
+                .collect();
+        }
+        active.len()
+    }
+
+    #[test]
+    fn test_conway_cubes() {
+        assert_eq!(conway_cubes::<3>(), 112);
+        assert_eq!(conway_cubes::<4>(), 848);
+    }
+}
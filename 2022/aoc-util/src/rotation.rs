@@ -0,0 +1,270 @@
+//! The rotations of a cube, exactly, as integer matrices: the 24 ways to turn a 3D scanner (2021
+//! day 19) or a die, and the 48 with mirror images. And lining up two sets of points that were
+//! seen from differently turned and placed scanners.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Mul;
+
+use crate::point::Point;
+
+/// A rotation (or, if [`is_proper`](Rotation::is_proper) is false, a reflection) that maps axes
+/// onto axes: a 3×3 matrix with a single ±1 in each row and column.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Rotation {
+    rows: [[i64; 3]; 3],
+}
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation {
+        rows: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+    };
+
+    /// The rotation whose row `i` picks out axis `axes[i]` of a point, times `signs[i]`. `None` if
+    /// `axes` isn't a permutation of `0..3`, or a sign isn't ±1.
+    pub fn from_axes(axes: [usize; 3], signs: [i64; 3]) -> Option<Rotation> {
+        let mut seen = [false; 3];
+        let mut rows = [[0; 3]; 3];
+        for (row, (&axis, &sign)) in axes.iter().zip(signs.iter()).enumerate() {
+            if 3 <= axis || seen[axis] || sign.abs() != 1 {
+                return None;
+            }
+            seen[axis] = true;
+            rows[row][axis] = sign;
+        }
+        Some(Rotation { rows })
+    }
+
+    /// A quarter turn counterclockwise around `axis`, looking down it towards the origin.
+    pub fn quarter_turn(axis: usize) -> Rotation {
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut rows = [[0; 3]; 3];
+        rows[axis][axis] = 1;
+        rows[a][b] = -1;
+        rows[b][a] = 1;
+        Rotation { rows }
+    }
+
+    /// The 24 proper rotations, starting with the identity.
+    pub fn all() -> impl Iterator<Item = Rotation> {
+        Rotation::all_with_reflections().filter(|r| r.is_proper())
+    }
+
+    /// All 48 rotations and reflections, starting with the identity.
+    pub fn all_with_reflections() -> impl Iterator<Item = Rotation> {
+        const PERMUTATIONS: [[usize; 3]; 6] =
+            [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+        PERMUTATIONS.iter().flat_map(|&axes| {
+            (0..8).map(move |bits| {
+                let sign = |bit: usize| if bits & (1 << bit) == 0 { 1 } else { -1 };
+                Rotation::from_axes(axes, [sign(0), sign(1), sign(2)]).unwrap()
+            })
+        })
+    }
+
+    pub fn determinant(&self) -> i64 {
+        let m = &self.rows;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Whether this is a rotation, rather than a reflection.
+    pub fn is_proper(&self) -> bool {
+        self.determinant() == 1
+    }
+
+    /// The rotation that undoes this one. (For these matrices, that's the transpose.)
+    pub fn inverse(&self) -> Rotation {
+        let mut rows = [[0; 3]; 3];
+        for (r, row) in self.rows.iter().enumerate() {
+            for (c, &v) in row.iter().enumerate() {
+                rows[c][r] = v;
+            }
+        }
+        Rotation { rows }
+    }
+
+    /// This rotation after `first`: `a.then_after(b).apply(p) == a.apply(b.apply(p))`.
+    pub fn then_after(&self, first: &Rotation) -> Rotation {
+        let mut rows = [[0; 3]; 3];
+        for (r, row) in rows.iter_mut().enumerate() {
+            for (c, v) in row.iter_mut().enumerate() {
+                *v = (0..3).map(|k| self.rows[r][k] * first.rows[k][c]).sum();
+            }
+        }
+        Rotation { rows }
+    }
+
+    pub fn apply(&self, point: Point<3>) -> Point<3> {
+        let mut rotated = Point::ORIGIN;
+        for (axis, row) in self.rows.iter().enumerate() {
+            rotated[axis] = row.iter().zip(point.0.iter()).map(|(m, v)| m * v).sum();
+        }
+        rotated
+    }
+}
+
+impl Default for Rotation {
+    fn default() -> Rotation {
+        Rotation::IDENTITY
+    }
+}
+
+/// Composition: `(a * b) * p == a * (b * p)`.
+impl Mul for Rotation {
+    type Output = Rotation;
+
+    fn mul(self, rhs: Rotation) -> Rotation {
+        self.then_after(&rhs)
+    }
+}
+
+impl Mul<Point<3>> for Rotation {
+    type Output = Point<3>;
+
+    fn mul(self, rhs: Point<3>) -> Point<3> {
+        self.apply(rhs)
+    }
+}
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |r: &[i64; 3]| format!("{:2} {:2} {:2}", r[0], r[1], r[2]);
+        write!(f, "[{}; {}; {}]", row(&self.rows[0]), row(&self.rows[1]), row(&self.rows[2]))
+    }
+}
+
+/// A rotation followed by a translation: where one scanner is, and which way it faces, as seen
+/// from another.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Alignment {
+    pub rotation: Rotation,
+    pub translation: Point<3>,
+}
+
+impl Alignment {
+    pub fn apply(&self, point: Point<3>) -> Point<3> {
+        self.rotation.apply(point) + self.translation
+    }
+
+    /// The alignment that maps points back.
+    pub fn inverse(&self) -> Alignment {
+        let rotation = self.rotation.inverse();
+        Alignment {
+            rotation,
+            translation: -rotation.apply(self.translation),
+        }
+    }
+
+    /// This alignment after `first`.
+    pub fn then_after(&self, first: &Alignment) -> Alignment {
+        Alignment {
+            rotation: self.rotation.then_after(&first.rotation),
+            translation: self.apply(first.translation),
+        }
+    }
+}
+
+/// Find a proper rotation and translation that map at least `min_overlap` of the points in
+/// `other` onto points in `reference`: 2021 day 19's "12 beacons in common". `None` if no
+/// rotation lines up that many.
+///
+/// Every pairing of a point in each set, under every rotation, votes for the translation that
+/// would line the pair up; a translation that gets `min_overlap` votes wins.
+pub fn align(reference: &[Point<3>], other: &[Point<3>], min_overlap: usize) -> Option<Alignment> {
+    let mut votes = HashMap::new();
+    for rotation in Rotation::all() {
+        votes.clear();
+        for p in other {
+            let rotated = rotation.apply(*p);
+            for r in reference {
+                let count = votes.entry(*r - rotated).or_insert(0);
+                *count += 1;
+                if *count == min_overlap {
+                    return Some(Alignment {
+                        rotation,
+                        translation: *r - rotated,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_group() {
+        let proper = Rotation::all().collect::<HashSet<_>>();
+        assert_eq!(proper.len(), 24);
+        assert_eq!(Rotation::all_with_reflections().collect::<HashSet<_>>().len(), 48);
+        assert_eq!(Rotation::all().next(), Some(Rotation::IDENTITY));
+
+        // Closed under composition, and every rotation has an inverse in the group.
+        for a in proper.iter() {
+            assert_eq!(*a * a.inverse(), Rotation::IDENTITY);
+            assert!(proper.contains(&a.inverse()));
+            for b in proper.iter() {
+                assert!(proper.contains(&(*a * *b)));
+            }
+        }
+
+        // The quarter turns generate the lot.
+        let mut generated = HashSet::new();
+        generated.insert(Rotation::IDENTITY);
+        let turns = [0, 1, 2].map(Rotation::quarter_turn);
+        while let Some(next) = generated
+            .iter()
+            .flat_map(|r| turns.iter().map(move |t| *t * *r))
+            .find(|r| !generated.contains(r))
+        {
+            generated.insert(next);
+        }
+        assert_eq!(generated, proper);
+    }
+
+    #[test]
+    fn test_apply() {
+        let p = Point([1, 2, 3]);
+        let z = Rotation::quarter_turn(2);
+        assert_eq!(z * p, Point([-2, 1, 3]));
+        assert_eq!((z * z * z * z) * p, p);
+        assert_eq!((z * Rotation::quarter_turn(0)) * p, z * (Rotation::quarter_turn(0) * p));
+        let mirror = Rotation::from_axes([0, 1, 2], [-1, 1, 1]).unwrap();
+        assert!(!mirror.is_proper());
+        assert_eq!(mirror * p, Point([-1, 2, 3]));
+        assert_eq!(Rotation::from_axes([0, 0, 2], [1, 1, 1]), None);
+        assert_eq!(Rotation::IDENTITY.to_string(), "[ 1  0  0;  0  1  0;  0  0  1]");
+    }
+
+    #[test]
+    fn test_align() {
+        // Two scanners' views of 20 shared beacons, plus a few each that the other can't see.
+        let mut seed = 7u64;
+        let mut random = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as i64 % 1000 - 500
+        };
+        let shared = (0..20).map(|_| Point([random(), random(), random()])).collect::<Vec<_>>();
+        let truth = Alignment {
+            rotation: Rotation::all().nth(17).unwrap(),
+            translation: Point([68, -1246, -43]),
+        };
+        let mut reference = shared.clone();
+        reference.extend((0..5).map(|_| Point([random(), random(), random()])));
+        let back = truth.inverse();
+        let mut other = (0..5).map(|_| Point([random(), random(), random()])).collect::<Vec<_>>();
+        other.extend(shared.iter().map(|p| back.apply(*p)));
+
+        let found = align(&reference, &other, 12).unwrap();
+        assert_eq!(found, truth);
+        assert_eq!(found.then_after(&found.inverse()).apply(Point([1, 2, 3])), Point([1, 2, 3]));
+        assert_eq!(align(&reference, &other, 21), None);
+    }
+}
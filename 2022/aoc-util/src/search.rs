@@ -0,0 +1,462 @@
+//! Graph search: BFS, Dijkstra, A* and DFS, over anything that can enumerate its neighbors.
+//!
+//! Implement [`SearchSpace`] for the puzzle's state (or wrap a closure with [`from_fn`]), or use
+//! [`MapSpace`] to search over the tiles of a [`Map`].
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Add;
+
+use crate::map::{BoundCoord, Map};
+
+/// What a path's cost can be: anything that adds up and orders. `Default` is taken to be zero.
+pub trait Cost: Copy + Ord + Default + Add<Output = Self> {}
+
+impl<C: Copy + Ord + Default + Add<Output = C>> Cost for C {}
+
+/// A space of states to search, with weighted edges between them.
+pub trait SearchSpace {
+    type State: Clone + Eq + Hash;
+    type Cost: Cost;
+
+    /// The states reachable in one step from `state`, and what it costs to step to each.
+    fn neighbors(&self, state: &Self::State) -> impl Iterator<Item = (Self::State, Self::Cost)>;
+}
+
+/// A path found by a search.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Path<S, C> {
+    /// The states along the path, from the start to the goal, inclusive.
+    pub states: Vec<S>,
+    /// The sum of the costs of the steps along the path.
+    pub cost: C,
+}
+
+/// A [`SearchSpace`] from a closure returning the neighbors of a state.
+pub struct FnSpace<F, S, C> {
+    neighbors: F,
+    _types: PhantomData<fn(&S) -> C>,
+}
+
+/// Make a [`SearchSpace`] from a closure returning the neighbors of a state.
+pub fn from_fn<F, S, C, I>(neighbors: F) -> FnSpace<F, S, C>
+where
+    F: Fn(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+{
+    FnSpace {
+        neighbors,
+        _types: PhantomData,
+    }
+}
+
+impl<F, S, C, I> SearchSpace for FnSpace<F, S, C>
+where
+    F: Fn(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    S: Clone + Eq + Hash,
+    C: Cost,
+{
+    type State = S;
+    type Cost = C;
+
+    fn neighbors(&self, state: &S) -> impl Iterator<Item = (S, C)> {
+        (self.neighbors)(state).into_iter()
+    }
+}
+
+/// Searching over the tiles of a [`Map`], stepping to adjacent tiles.
+///
+/// `cost(from, to)` is the cost of stepping from a tile onto an adjacent one, or `None` if that
+/// step can't be taken.
+pub struct MapSpace<'a, T, F> {
+    map: &'a Map<T>,
+    cost: F,
+    diagonals: bool,
+}
+
+impl<'a, T, F, C> MapSpace<'a, T, F>
+where
+    F: Fn(&T, &T) -> Option<C>,
+    C: Cost,
+{
+    /// Step only in the cardinal directions (see [`BoundCoord::adj_cardinal`]).
+    pub fn cardinal(map: &'a Map<T>, cost: F) -> MapSpace<'a, T, F> {
+        MapSpace {
+            map,
+            cost,
+            diagonals: false,
+        }
+    }
+
+    /// Step diagonally, too (see [`BoundCoord::adj_diags`]).
+    pub fn with_diags(map: &'a Map<T>, cost: F) -> MapSpace<'a, T, F> {
+        MapSpace {
+            map,
+            cost,
+            diagonals: true,
+        }
+    }
+}
+
+impl<T, F, C> SearchSpace for MapSpace<'_, T, F>
+where
+    F: Fn(&T, &T) -> Option<C>,
+    C: Cost,
+{
+    type State = BoundCoord;
+    type Cost = C;
+
+    fn neighbors(&self, state: &BoundCoord) -> impl Iterator<Item = (BoundCoord, C)> {
+        let adj: Box<dyn Iterator<Item = BoundCoord>> = if self.diagonals {
+            Box::new(state.adj_diags())
+        } else {
+            Box::new(state.adj_cardinal())
+        };
+        let from = self.map.at(state).expect("searched from a coordinate off the map");
+        adj.filter_map(move |to| {
+            let cost = (self.cost)(from, self.map.at(&to)?)?;
+            Some((to, cost))
+        })
+    }
+}
+
+/// The Manhattan distance between two tiles; an admissible A* heuristic on a [`MapSpace`] that
+/// only steps cardinally, and where each step costs at least 1.
+pub fn manhattan(a: &BoundCoord, b: &BoundCoord) -> usize {
+    a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+}
+
+/// Walk `came_from` back from `goal`, returning the states from the start to `goal`.
+pub fn reconstruct_path<S: Clone + Eq + Hash>(came_from: &HashMap<S, S>, goal: S) -> Vec<S> {
+    let mut path = vec![goal];
+    while let Some(prev) = came_from.get(path.last().unwrap()) {
+        path.push(prev.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Breadth-first search for the path with the fewest steps from `start` to a goal. (Costs are
+/// ignored while searching, but summed along the found path.)
+pub fn bfs<G, P>(space: &G, start: G::State, mut is_goal: P) -> Option<Path<G::State, G::Cost>>
+where
+    G: SearchSpace,
+    P: FnMut(&G::State) -> bool,
+{
+    let mut came_from = HashMap::new();
+    let mut cost_to = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert(start.clone());
+    cost_to.insert(start.clone(), G::Cost::default());
+    queue.push_back(start);
+
+    while let Some(state) = queue.pop_front() {
+        if is_goal(&state) {
+            let cost = cost_to[&state];
+            return Some(Path {
+                states: reconstruct_path(&came_from, state),
+                cost,
+            });
+        }
+        let cost = cost_to[&state];
+        for (next, step_cost) in space.neighbors(&state) {
+            if seen.insert(next.clone()) {
+                came_from.insert(next.clone(), state.clone());
+                cost_to.insert(next.clone(), cost + step_cost);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Every state reachable from `start`, with the fewest steps it takes to reach it. (A flood
+/// fill, on a [`MapSpace`].)
+pub fn reachable<G: SearchSpace>(space: &G, start: G::State) -> HashMap<G::State, usize> {
+    let mut steps = HashMap::new();
+    let mut queue = VecDeque::new();
+    steps.insert(start.clone(), 0);
+    queue.push_back(start);
+    while let Some(state) = queue.pop_front() {
+        let here = steps[&state];
+        for (next, _) in space.neighbors(&state) {
+            if !steps.contains_key(&next) {
+                steps.insert(next.clone(), here + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    steps
+}
+
+/// An entry in the open set of Dijkstra's & A*: a state, ordered by (lowest) priority first.
+struct Open<S, C> {
+    priority: C,
+    state: S,
+}
+
+impl<S, C: Ord> PartialEq for Open<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S, C: Ord> Eq for Open<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Open<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Open<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, as `BinaryHeap` is a max-heap.
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/// Dijkstra's algorithm: the cheapest path from `start` to a goal.
+pub fn dijkstra<G, P>(space: &G, start: G::State, is_goal: P) -> Option<Path<G::State, G::Cost>>
+where
+    G: SearchSpace,
+    P: FnMut(&G::State) -> bool,
+{
+    astar(space, start, is_goal, |_| G::Cost::default())
+}
+
+/// A*: the cheapest path from `start` to a goal, guided by `heuristic`.
+///
+/// `heuristic` estimates the cost from a state to the nearest goal; for the path found to be the
+/// cheapest, it must never overestimate.
+pub fn astar<G, P, H>(
+    space: &G,
+    start: G::State,
+    mut is_goal: P,
+    heuristic: H,
+) -> Option<Path<G::State, G::Cost>>
+where
+    G: SearchSpace,
+    P: FnMut(&G::State) -> bool,
+    H: Fn(&G::State) -> G::Cost,
+{
+    let mut came_from = HashMap::new();
+    let mut cost_to = HashMap::new();
+    let mut open = BinaryHeap::new();
+    cost_to.insert(start.clone(), G::Cost::default());
+    open.push(Open {
+        priority: heuristic(&start),
+        state: start,
+    });
+
+    while let Some(Open { priority, state }) = open.pop() {
+        let cost = cost_to[&state];
+        // Skip entries made stale by finding a cheaper way here after they were pushed.
+        if cost + heuristic(&state) < priority {
+            continue;
+        }
+        if is_goal(&state) {
+            return Some(Path {
+                states: reconstruct_path(&came_from, state),
+                cost,
+            });
+        }
+        for (next, step_cost) in space.neighbors(&state) {
+            let tentative = cost + step_cost;
+            let is_better = cost_to.get(&next).map(|&c| tentative < c).unwrap_or(true);
+            if is_better {
+                came_from.insert(next.clone(), state.clone());
+                cost_to.insert(next.clone(), tentative);
+                open.push(Open {
+                    priority: tentative + heuristic(&next),
+                    state: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Depth-first search for some path (not necessarily the shortest) from `start` to a goal.
+pub fn dfs<G, P>(space: &G, start: G::State, mut is_goal: P) -> Option<Path<G::State, G::Cost>>
+where
+    G: SearchSpace,
+    P: FnMut(&G::State) -> bool,
+{
+    let mut came_from = HashMap::new();
+    let mut cost_to = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut stack = vec![start.clone()];
+    cost_to.insert(start, G::Cost::default());
+
+    while let Some(state) = stack.pop() {
+        if !seen.insert(state.clone()) {
+            continue;
+        }
+        if is_goal(&state) {
+            let cost = cost_to[&state];
+            return Some(Path {
+                states: reconstruct_path(&came_from, state),
+                cost,
+            });
+        }
+        let cost = cost_to[&state];
+        for (next, step_cost) in space.neighbors(&state) {
+            if !seen.contains(&next) {
+                came_from.insert(next.clone(), state.clone());
+                cost_to.insert(next.clone(), cost + step_cost);
+                stack.push(next);
+            }
+        }
+    }
+    None
+}
+
+/// Count the distinct paths from `start` to a goal, by depth-first search. A path ends at the
+/// first goal it reaches, and never revisits a state.
+///
+/// This enumerates every path, so the space had better be small, or acyclic and narrow.
+pub fn count_paths<G, P>(space: &G, start: G::State, mut is_goal: P) -> usize
+where
+    G: SearchSpace,
+    P: FnMut(&G::State) -> bool,
+{
+    fn visit<G: SearchSpace, P: FnMut(&G::State) -> bool>(
+        space: &G,
+        state: G::State,
+        is_goal: &mut P,
+        on_path: &mut HashSet<G::State>,
+    ) -> usize {
+        if is_goal(&state) {
+            return 1;
+        }
+        on_path.insert(state.clone());
+        let mut count = 0;
+        for (next, _) in space.neighbors(&state) {
+            if !on_path.contains(&next) {
+                count += visit(space, next, is_goal, on_path);
+            }
+        }
+        on_path.remove(&state);
+        count
+    }
+
+    visit(space, start, &mut is_goal, &mut HashSet::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::map::FreeCoord;
+
+    /// 2021 day 15's example: the lowest total risk from the top left to the bottom right is 40.
+    const RISK_EXAMPLE: &str = "\
+1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
+    fn risk_map() -> Map<u8> {
+        let rows = RISK_EXAMPLE
+            .lines()
+            .map(|l| l.chars().map(|c| c.to_digit(10).unwrap() as u8).collect())
+            .collect();
+        Map::from_rows(rows)
+    }
+
+    fn corners<T>(map: &Map<T>) -> (BoundCoord, BoundCoord) {
+        let start = FreeCoord { x: 0, y: 0 }.bind(map).unwrap();
+        let goal = FreeCoord {
+            x: map.width() as i64 - 1,
+            y: map.height() as i64 - 1,
+        };
+        (start, goal.bind(map).unwrap())
+    }
+
+    #[test]
+    fn test_dijkstra_and_astar_on_map() {
+        let map = risk_map();
+        let space = MapSpace::cardinal(&map, |_, to| Some(u32::from(*to)));
+        let (start, goal) = corners(&map);
+
+        let path = dijkstra(&space, start.clone(), |c| *c == goal).unwrap();
+        assert_eq!(path.cost, 40);
+        assert_eq!(path.states.first(), Some(&start));
+        assert_eq!(path.states.last(), Some(&goal));
+        let risk = path.states[1..].iter().map(|c| u32::from(*map.at(c).unwrap())).sum::<u32>();
+        assert_eq!(risk, 40);
+
+        let path = astar(&space, start, |c| *c == goal, |c| manhattan(c, &goal) as u32).unwrap();
+        assert_eq!(path.cost, 40);
+    }
+
+    #[test]
+    fn test_bfs_and_dfs_around_walls() {
+        let map = Map::from_rows(
+            ["..#.", ".##.", "...."]
+                .iter()
+                .map(|r| r.chars().map(|c| c == '#').collect())
+                .collect(),
+        );
+        let space = MapSpace::cardinal(&map, |_, &wall| if wall { None } else { Some(1) });
+        let start = FreeCoord { x: 0, y: 0 }.bind(&map).unwrap();
+        let goal = FreeCoord { x: 3, y: 0 }.bind(&map).unwrap();
+
+        let path = bfs(&space, start.clone(), |c| *c == goal).unwrap();
+        assert_eq!(path.states.len(), 8);
+        assert_eq!(path.cost, 7);
+        assert!(path.states.iter().all(|c| !map.at(c).unwrap()));
+
+        let path = dfs(&space, start.clone(), |c| *c == goal).unwrap();
+        assert_eq!(path.states.last(), Some(&goal));
+        assert!(path.states.windows(2).all(|w| manhattan(&w[0], &w[1]) == 1));
+
+        assert_eq!(reachable(&space, start.clone()).len(), 9);
+        let diag_space = MapSpace::with_diags(&map, |_, &wall| if wall { None } else { Some(1) });
+        assert_eq!(bfs(&diag_space, start.clone(), |c| *c == goal).unwrap().cost, 5);
+
+        // Walls can't be stepped onto, so can't be found.
+        assert!(bfs(&space, start, |c| map.at(c) == Some(&true)).is_none());
+    }
+
+    #[test]
+    fn test_count_paths_through_caves() {
+        // 2021 day 12's first example: 10 paths, visiting small caves at most once.
+        let edges = [
+            ("start", "A"),
+            ("start", "b"),
+            ("A", "c"),
+            ("A", "b"),
+            ("b", "d"),
+            ("A", "end"),
+            ("b", "end"),
+        ];
+        let space = from_fn(|(cave, visited): &(&str, Vec<&str>)| {
+            edges
+                .iter()
+                .flat_map(|&(a, b)| [(a, b), (b, a)])
+                .filter(|&(from, to)| from == *cave && !visited.contains(&to))
+                .map(|(_, to)| {
+                    let mut visited = visited.clone();
+                    if to.chars().all(|c| c.is_ascii_lowercase()) {
+                        visited.push(to);
+                    }
+                    ((to, visited), 1u32)
+                })
+                .collect::<Vec<_>>()
+        });
+        let count = count_paths(&space, ("start", vec!["start"]), |(cave, _)| *cave == "end");
+        assert_eq!(count, 10);
+    }
+}
@@ -0,0 +1,302 @@
+//! A sparse, unbounded grid, for maps that grow (or were never bounded to begin with).
+//!
+//! Only cells that differ from the map's background are stored, so a `SparseMap<bool>` with a
+//! background of `false` is a set of coordinates, and 2021 day 20's infinite image is a map whose
+//! background flips between steps.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+
+use crate::map::{FreeCoord, Map};
+
+/// An inclusive bounding box.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Bounds {
+    pub min: FreeCoord,
+    pub max: FreeCoord,
+}
+
+impl Bounds {
+    /// A box holding just `coord`.
+    pub fn point(coord: FreeCoord) -> Bounds {
+        Bounds {
+            min: coord,
+            max: coord,
+        }
+    }
+
+    /// The smallest box holding both this box and `coord`.
+    pub fn grow_to(self, coord: FreeCoord) -> Bounds {
+        Bounds {
+            min: FreeCoord {
+                x: self.min.x.min(coord.x),
+                y: self.min.y.min(coord.y),
+            },
+            max: FreeCoord {
+                x: self.max.x.max(coord.x),
+                y: self.max.y.max(coord.y),
+            },
+        }
+    }
+
+    pub fn contains(&self, coord: FreeCoord) -> bool {
+        let in_x = self.min.x <= coord.x && coord.x <= self.max.x;
+        let in_y = self.min.y <= coord.y && coord.y <= self.max.y;
+        in_x && in_y
+    }
+
+    pub fn width(&self) -> usize {
+        usize::try_from(self.max.x - self.min.x + 1).unwrap()
+    }
+
+    pub fn height(&self) -> usize {
+        usize::try_from(self.max.y - self.min.y + 1).unwrap()
+    }
+}
+
+/// A grid keyed by [`FreeCoord`], where every cell not explicitly set is the background.
+#[derive(Clone, Debug)]
+pub struct SparseMap<T> {
+    cells: HashMap<FreeCoord, T>,
+    background: T,
+    /// The bounds of `cells` (`None` if there are none), unless `bounds_stale` is set, in which
+    /// case they need recomputing, after a cell on the edge was cleared.
+    bounds: Cell<Option<Bounds>>,
+    bounds_stale: Cell<bool>,
+}
+
+impl<T: Clone + PartialEq> SparseMap<T> {
+    /// An empty map: every cell is `background`.
+    pub fn new(background: T) -> SparseMap<T> {
+        SparseMap {
+            cells: HashMap::new(),
+            background,
+            bounds: Cell::new(None),
+            bounds_stale: Cell::new(false),
+        }
+    }
+
+    /// The value at `coord`; the background if it was never set.
+    pub fn get(&self, coord: FreeCoord) -> &T {
+        self.cells.get(&coord).unwrap_or(&self.background)
+    }
+
+    /// Set the value at `coord`. Setting it to the background clears it.
+    pub fn set(&mut self, coord: FreeCoord, value: T) {
+        if value == self.background {
+            self.clear(coord);
+            return;
+        }
+        self.cells.insert(coord, value);
+        if !self.bounds_stale.get() {
+            let bounds = match self.bounds.get() {
+                Some(b) => b.grow_to(coord),
+                None => Bounds::point(coord),
+            };
+            self.bounds.set(Some(bounds));
+        }
+    }
+
+    /// Reset `coord` to the background.
+    pub fn clear(&mut self, coord: FreeCoord) {
+        if self.cells.remove(&coord).is_some() {
+            // Only a cell on the edge of the bounds can shrink them; work that out lazily.
+            if let Some(b) = self.bounds.get() {
+                let on_edge = coord.x == b.min.x
+                    || coord.x == b.max.x
+                    || coord.y == b.min.y
+                    || coord.y == b.max.y;
+                if on_edge {
+                    self.bounds_stale.set(true);
+                }
+            }
+        }
+    }
+
+    pub fn background(&self) -> &T {
+        &self.background
+    }
+
+    /// Change the background. Cells already set to the new background are cleared; cells that
+    /// were background keep the old background as their value only if they're inside the current
+    /// bounds, as the infinite region outside can't be stored.
+    ///
+    /// For a map like 2021 day 20's, where every cell changes each step, it's usually simpler to
+    /// build the next map with [`SparseMap::new`] and the next background.
+    pub fn set_background(&mut self, background: T) {
+        let old = std::mem::replace(&mut self.background, background);
+        if let Some(bounds) = self.bounds() {
+            for y in bounds.min.y..=bounds.max.y {
+                for x in bounds.min.x..=bounds.max.x {
+                    self.cells.entry(FreeCoord { x, y }).or_insert_with(|| old.clone());
+                }
+            }
+        }
+        let background = &self.background;
+        self.cells.retain(|_, v| v != background);
+        self.bounds_stale.set(true);
+    }
+
+    /// Clear every cell and change the background, keeping the allocation for reuse.
+    pub fn reset(&mut self, background: T) {
+        self.cells.clear();
+        self.background = background;
+        self.bounds.set(None);
+        self.bounds_stale.set(false);
+    }
+
+    /// The number of cells that aren't the background.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Iterate over the cells that aren't the background, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (FreeCoord, &T)> {
+        self.cells.iter().map(|(c, v)| (*c, v))
+    }
+
+    /// The smallest box holding every cell that isn't the background; `None` if there are none.
+    pub fn bounds(&self) -> Option<Bounds> {
+        if self.bounds_stale.get() {
+            let bounds = self.cells.keys().fold(None, |b: Option<Bounds>, &c| {
+                Some(b.map_or_else(|| Bounds::point(c), |b| b.grow_to(c)))
+            });
+            self.bounds.set(bounds);
+            self.bounds_stale.set(false);
+        }
+        self.bounds.get()
+    }
+
+    /// Convert to a dense [`Map`] covering [`SparseMap::bounds`], along with the coordinate of the
+    /// dense map's top left tile. An empty map becomes a 0 × 0 map at the origin.
+    pub fn to_map(&self) -> (Map<T>, FreeCoord) {
+        match self.bounds() {
+            Some(bounds) => {
+                let map = Map::from_fn(bounds.width(), bounds.height(), |x, y| {
+                    let coord = bounds.min.offset(x as i64, y as i64);
+                    self.get(coord).clone()
+                });
+                (map, bounds.min)
+            }
+            None => (Map::from_rows(Vec::new()), FreeCoord { x: 0, y: 0 }),
+        }
+    }
+
+    /// Convert from a dense [`Map`], whose top left tile is at the origin.
+    pub fn from_map(map: &Map<T>, background: T) -> SparseMap<T> {
+        let mut sparse = SparseMap::new(background);
+        for (coord, tile) in map.tiles() {
+            sparse.set(coord.unbind(), tile.clone());
+        }
+        sparse
+    }
+
+    /// Print the cells inside the bounds to stdout, one character per cell, as
+    /// [`Map::render_single_char`] does.
+    pub fn render_single_char<F: Fn(&T) -> char>(&self, df: F) {
+        self.to_map().0.render_single_char(df)
+    }
+
+    /// The cells inside the bounds as a string, one character per cell, as
+    /// [`Map::to_string_single_char`] does.
+    pub fn to_string_single_char<F: Fn(&T) -> char>(&self, df: F) -> String {
+        self.to_map().0.to_string_single_char(df)
+    }
+}
+
+impl<T: Clone + PartialEq + Default> Default for SparseMap<T> {
+    fn default() -> SparseMap<T> {
+        SparseMap::new(T::default())
+    }
+}
+
+/// Collect cells into a map with the default background.
+impl<T: Clone + PartialEq + Default> FromIterator<(FreeCoord, T)> for SparseMap<T> {
+    fn from_iter<I: IntoIterator<Item = (FreeCoord, T)>>(iter: I) -> SparseMap<T> {
+        let mut map = SparseMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<T: Clone + PartialEq> Extend<(FreeCoord, T)> for SparseMap<T> {
+    fn extend<I: IntoIterator<Item = (FreeCoord, T)>>(&mut self, iter: I) {
+        for (coord, value) in iter {
+            self.set(coord, value);
+        }
+    }
+}
+
+/// Collect a set of coordinates into a map that's `true` at each of them.
+impl FromIterator<FreeCoord> for SparseMap<bool> {
+    fn from_iter<I: IntoIterator<Item = FreeCoord>>(iter: I) -> SparseMap<bool> {
+        iter.into_iter().map(|c| (c, true)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(x: i64, y: i64) -> FreeCoord {
+        FreeCoord { x, y }
+    }
+
+    #[test]
+    fn test_get_set_bounds() {
+        let mut map = SparseMap::new('.');
+        assert_eq!(map.bounds(), None);
+        assert_eq!(*map.get(c(-100, 7)), '.');
+
+        map.set(c(-2, 3), '#');
+        map.set(c(4, -1), '#');
+        map.set(c(0, 0), '#');
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.bounds(), Some(Bounds { min: c(-2, -1), max: c(4, 3) }));
+
+        // Clearing a cell on the edge shrinks the bounds; setting one to the background clears it.
+        map.set(c(4, -1), '.');
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.bounds(), Some(Bounds { min: c(-2, 0), max: c(0, 3) }));
+        map.clear(c(-2, 3));
+        map.clear(c(0, 0));
+        assert!(map.is_empty());
+        assert_eq!(map.bounds(), None);
+    }
+
+    #[test]
+    fn test_dense_round_trip() {
+        let dots = [c(-1, -1), c(1, 0), c(-1, 1)].iter().copied().collect::<SparseMap<bool>>();
+        let (dense, origin) = dots.to_map();
+        assert_eq!(origin, c(-1, -1));
+        assert_eq!((dense.width(), dense.height()), (3, 3));
+        let render = |b: &bool| if *b { '#' } else { '.' };
+        assert_eq!(dense.to_string_single_char(render), "#..\n..#\n#..\n");
+        assert_eq!(dots.to_string_single_char(render), "#..\n..#\n#..\n");
+
+        let back = SparseMap::from_map(&dense, false);
+        let mut cells = back.iter().map(|(c, _)| c).collect::<Vec<_>>();
+        cells.sort_by_key(|c| (c.y, c.x));
+        assert_eq!(cells, [c(0, 0), c(2, 1), c(0, 2)]);
+    }
+
+    #[test]
+    fn test_set_background() {
+        // Like 2021 day 20: an "infinite" image whose background lights up.
+        let mut image = [c(0, 0), c(1, 1)].iter().copied().collect::<SparseMap<bool>>();
+        image.set_background(true);
+        assert!(*image.get(c(50, 50)));
+        assert!(*image.get(c(0, 0)));
+        assert!(!*image.get(c(1, 0)));
+        assert!(!*image.get(c(0, 1)));
+        assert_eq!(image.len(), 2);
+        assert_eq!(image.bounds(), Some(Bounds { min: c(0, 0), max: c(1, 1) }));
+        assert_eq!(image.to_string_single_char(|b| if *b { '#' } else { '.' }), "#.\n.#\n");
+    }
+}
@@ -1,11 +1,42 @@
+//! Styling text for the terminal.
+//!
+//! [`Styled`] wraps anything that's `Display`, and writes it with escape sequences around it, but
+//! only if the output is a terminal and `NO_COLOR` isn't set.
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io::IsTerminal;
+
+use once_cell::sync::Lazy;
+
 /// Attributes text on a XTerm can be styled with
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
 pub struct TermAttr {
     pub bold: bool,
     pub underlined: bool,
     pub color: Option<TermColor>,
 }
 
+impl TermAttr {
+    /// Just a color.
+    pub fn color(color: TermColor) -> TermAttr {
+        TermAttr {
+            color: Some(color),
+            ..TermAttr::default()
+        }
+    }
+
+    /// The escape sequence that turns these attributes on; empty if there aren't any.
+    pub fn escape(&self) -> String {
+        Style::from(*self).escape()
+    }
+}
+
+/// The escape sequence that resets all attributes.
+pub const RESET: &str = "\x1b[0m";
+
 /// The XTerm colors
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum TermColor {
     Black,
     Red,
@@ -47,4 +78,242 @@ impl TermColor {
             BrightWhite => "97",
         }
     }
+
+    /// The code for this color as the background.
+    pub fn as_xterm_bg_attr(&self) -> &'static str {
+        use TermColor::*;
+        match self {
+            Black => "40",
+            Red => "41",
+            Green => "42",
+            Yellow => "43",
+            Blue => "44",
+            Magenta => "45",
+            Cyan => "46",
+            White => "47",
+            BrightBlack => "100",
+            BrightRed => "101",
+            BrightGreen => "102",
+            BrightYellow => "103",
+            BrightBlue => "104",
+            BrightMagenta => "105",
+            BrightCyan => "106",
+            BrightWhite => "107",
+        }
+    }
+}
+
+/// Any color a terminal can show.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Color {
+    /// One of the 16 basic colors, which follow the terminal's theme.
+    Basic(TermColor),
+    /// One of the 256 colors of the XTerm palette.
+    Ansi256(u8),
+    /// A "true color" red, green and blue.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    fn code(&self, background: bool) -> String {
+        let extended = if background { "48" } else { "38" };
+        match self {
+            Color::Basic(c) if background => c.as_xterm_bg_attr().to_owned(),
+            Color::Basic(c) => c.as_xterm_attr().to_owned(),
+            Color::Ansi256(n) => format!("{};5;{}", extended, n),
+            Color::Rgb(r, g, b) => format!("{};2;{};{};{}", extended, r, g, b),
+        }
+    }
+}
+
+impl From<TermColor> for Color {
+    fn from(color: TermColor) -> Color {
+        Color::Basic(color)
+    }
+}
+
+/// Everything [`Styled`] can do to some text.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Style {
+    pub bold: bool,
+    pub underlined: bool,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Style {
+    /// The escape sequence that turns this style on; empty if it's the default.
+    pub fn escape(&self) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push("1".to_owned());
+        }
+        if self.underlined {
+            codes.push("4".to_owned());
+        }
+        codes.extend(self.fg.map(|c| c.code(false)));
+        codes.extend(self.bg.map(|c| c.code(true)));
+        if codes.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{}m", codes.join(";"))
+        }
+    }
+}
+
+impl From<TermAttr> for Style {
+    fn from(attr: TermAttr) -> Style {
+        Style {
+            bold: attr.bold,
+            underlined: attr.underlined,
+            fg: attr.color.map(Color::Basic),
+            bg: None,
+        }
+    }
+}
+
+/// Where styled text is headed, which decides whether it gets styled at all.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Output {
+    /// Style it if stdout is a terminal, and `NO_COLOR` isn't set.
+    Stdout,
+    /// Style it if stderr is a terminal, and `NO_COLOR` isn't set.
+    Stderr,
+    /// Style it regardless, as when the text is headed somewhere that's known to want it.
+    Always,
+    /// Never style it.
+    Never,
+}
+
+impl Output {
+    pub fn colors_enabled(&self) -> bool {
+        fn no_color() -> Option<OsString> {
+            std::env::var_os("NO_COLOR")
+        }
+        static STDOUT: Lazy<bool> =
+            Lazy::new(|| colors_enabled(no_color().as_deref(), std::io::stdout().is_terminal()));
+        static STDERR: Lazy<bool> =
+            Lazy::new(|| colors_enabled(no_color().as_deref(), std::io::stderr().is_terminal()));
+        match self {
+            Output::Stdout => *STDOUT,
+            Output::Stderr => *STDERR,
+            Output::Always => true,
+            Output::Never => false,
+        }
+    }
+}
+
+/// Per <https://no-color.org>, an empty `NO_COLOR` doesn't count.
+fn colors_enabled(no_color: Option<&OsStr>, is_terminal: bool) -> bool {
+    let no_color = no_color.is_some_and(|v| !v.is_empty());
+    is_terminal && !no_color
+}
+
+/// A value that displays with a style, like `Styled::new("FAILED").bold().fg(TermColor::Red)`.
+///
+/// By default it's styled only if stdout is a terminal; see [`Styled::on`].
+#[derive(Clone, Copy, Debug)]
+pub struct Styled<T> {
+    value: T,
+    style: Style,
+    output: Output,
+}
+
+impl<T> Styled<T> {
+    pub fn new(value: T) -> Styled<T> {
+        Styled {
+            value,
+            style: Style::default(),
+            output: Output::Stdout,
+        }
+    }
+
+    pub fn bold(mut self) -> Styled<T> {
+        self.style.bold = true;
+        self
+    }
+
+    pub fn underlined(mut self) -> Styled<T> {
+        self.style.underlined = true;
+        self
+    }
+
+    /// Set the foreground color: a [`TermColor`], or any other [`Color`].
+    pub fn fg<C: Into<Color>>(mut self, color: C) -> Styled<T> {
+        self.style.fg = Some(color.into());
+        self
+    }
+
+    /// Set the background color: a [`TermColor`], or any other [`Color`].
+    pub fn bg<C: Into<Color>>(mut self, color: C) -> Styled<T> {
+        self.style.bg = Some(color.into());
+        self
+    }
+
+    /// Replace the whole style.
+    pub fn style<S: Into<Style>>(mut self, style: S) -> Styled<T> {
+        self.style = style.into();
+        self
+    }
+
+    /// Say where the text is headed (stdout, if not set).
+    pub fn on(mut self, output: Output) -> Styled<T> {
+        self.output = output;
+        self
+    }
+}
+
+/// Formatting flags, like a width, apply to the value, inside the escapes.
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let escape = self.style.escape();
+        if escape.is_empty() || !self.output.colors_enabled() {
+            return self.value.fmt(f);
+        }
+        f.write_str(&escape)?;
+        self.value.fmt(f)?;
+        f.write_str(RESET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escapes() {
+        let always = |s: Styled<&str>| s.on(Output::Always).to_string();
+        assert_eq!(always(Styled::new("x")), "x");
+        assert_eq!(always(Styled::new("x").bold().fg(TermColor::Red)), "\x1b[1;31mx\x1b[0m");
+        assert_eq!(
+            always(Styled::new("x").underlined().fg(Color::Ansi256(208)).bg(TermColor::Blue)),
+            "\x1b[4;38;5;208;44mx\x1b[0m",
+        );
+        assert_eq!(
+            always(Styled::new("x").fg(Color::Rgb(1, 2, 3)).bg(Color::Rgb(4, 5, 6))),
+            "\x1b[38;2;1;2;3;48;2;4;5;6mx\x1b[0m",
+        );
+        assert_eq!(always(Styled::new("x").bg(TermColor::BrightWhite)), "\x1b[107mx\x1b[0m");
+        let attr = TermAttr {
+            bold: true,
+            ..TermAttr::color(TermColor::Green)
+        };
+        assert_eq!(attr.escape(), "\x1b[1;32m");
+        assert_eq!(TermAttr::default().escape(), "");
+    }
+
+    #[test]
+    fn test_display_passthrough() {
+        let styled = Styled::new(42).fg(TermColor::Cyan);
+        assert_eq!(format!("[{:>4}]", styled.on(Output::Never)), "[  42]");
+        assert_eq!(format!("[{:>4}]", styled.on(Output::Always)), "[\x1b[36m  42\x1b[0m]");
+    }
+
+    #[test]
+    fn test_colors_enabled() {
+        assert!(colors_enabled(None, true));
+        assert!(colors_enabled(Some(OsStr::new("")), true));
+        assert!(!colors_enabled(Some(OsStr::new("1")), true));
+        assert!(!colors_enabled(None, false));
+    }
 }
@@ -0,0 +1,166 @@
+//! Animating a simulation in the terminal: each frame is drawn over the last.
+//!
+//! Run a solution with `--viz` (and optionally `--fps`), and have it call `viz().frame(&map)`
+//! each step. Without `--viz`, `frame` does nothing, so the calls can stay in.
+
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::OnceCell;
+
+use crate::map::{DisplayTile, Map};
+use crate::sparse::SparseMap;
+use crate::term::Output;
+
+static VIZ: OnceCell<Viz> = OnceCell::new();
+
+/// Set up the visualizer [`viz()`] returns. [`main_stub`](crate::main_stub) does this from the
+/// command line; it has no effect once the visualizer has been set up, or used.
+pub fn init(viz: Viz) {
+    let _ = VIZ.set(viz);
+}
+
+/// The visualizer; it's disabled unless [`init`] enabled it.
+pub fn viz() -> &'static Viz {
+    VIZ.get_or_init(Viz::disabled)
+}
+
+/// Something that can be drawn as a frame.
+pub trait Frame {
+    fn draw(&self, out: &mut Vec<u8>) -> io::Result<()>;
+}
+
+impl<T: DisplayTile> Frame for Map<T> {
+    fn draw(&self, out: &mut Vec<u8>) -> io::Result<()> {
+        self.render(out, Output::Stdout)
+    }
+}
+
+impl<T: DisplayTile + Clone + PartialEq> Frame for SparseMap<T> {
+    fn draw(&self, out: &mut Vec<u8>) -> io::Result<()> {
+        self.to_map().0.render(out, Output::Stdout)
+    }
+}
+
+/// Text, like a drawing of 2022 day 5's crate stacks.
+impl Frame for str {
+    fn draw(&self, out: &mut Vec<u8>) -> io::Result<()> {
+        out.extend_from_slice(self.as_bytes());
+        if !self.is_empty() && !self.ends_with('\n') {
+            out.push(b'\n');
+        }
+        Ok(())
+    }
+}
+
+impl Frame for String {
+    fn draw(&self, out: &mut Vec<u8>) -> io::Result<()> {
+        self.as_str().draw(out)
+    }
+}
+
+/// Draws frames, each over the last, at no more than a set frame rate.
+pub struct Viz {
+    enabled: bool,
+    frame_time: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    /// When the last frame was drawn.
+    last_frame: Option<Instant>,
+    /// How many lines the last frame took up, to move back up over.
+    lines: usize,
+}
+
+impl Viz {
+    /// A visualizer drawing at most `fps` frames a second, if `enabled`.
+    pub fn new(enabled: bool, fps: f64) -> Viz {
+        assert!(0. < fps, "frame rate must be positive, not {}", fps);
+        Viz {
+            enabled,
+            frame_time: Duration::from_secs_f64(1. / fps),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// A visualizer that draws nothing.
+    pub fn disabled() -> Viz {
+        Viz::new(false, 1.)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Draw `frame` to stdout, over the previous frame, waiting first if the previous frame hasn't
+    /// been up for long enough yet.
+    pub fn frame<F: Frame + ?Sized>(&self, frame: &F) {
+        if !self.enabled {
+            return;
+        }
+        let stdout = io::stdout();
+        self.draw_to(&mut stdout.lock(), frame).expect("failed to draw a frame to stdout");
+    }
+
+    /// Leave the last frame on screen: the next one is drawn below it rather than over it.
+    pub fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.lines = 0;
+    }
+
+    fn draw_to<W: Write, F: Frame + ?Sized>(&self, out: &mut W, frame: &F) -> io::Result<()> {
+        let mut body = Vec::new();
+        frame.draw(&mut body)?;
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(last) = state.last_frame {
+            if let Some(wait) = self.frame_time.checked_sub(last.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+
+        let mut buf = Vec::with_capacity(body.len() + 16);
+        if 0 < state.lines {
+            // Move up to the first line of the last frame, and clear from there down.
+            write!(buf, "\x1b[{}F\x1b[J", state.lines)?;
+        }
+        buf.extend_from_slice(&body);
+        out.write_all(&buf)?;
+        out.flush()?;
+
+        state.lines = body.iter().filter(|&&b| b == b'\n').count();
+        state.last_frame = Some(Instant::now());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redraw_in_place() {
+        let viz = Viz::new(true, 1000.);
+        let mut out = Vec::new();
+        viz.draw_to(&mut out, "ab\ncd").unwrap();
+        viz.draw_to(&mut out, &Map::from_rows(vec![vec!['x', 'y']])).unwrap();
+        viz.finish();
+        viz.draw_to(&mut out, "z\n").unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "ab\ncd\n\x1b[2F\x1b[Jxy\nz\n");
+    }
+
+    #[test]
+    fn test_frame_rate() {
+        let viz = Viz::new(true, 50.);
+        let start = Instant::now();
+        for _ in 0..3 {
+            viz.draw_to(&mut io::sink(), "frame").unwrap();
+        }
+        // The first frame is drawn right away; the next two each wait out 20ms.
+        assert!(Duration::from_millis(40) <= start.elapsed());
+    }
+}
@@ -18,7 +18,7 @@ fn find_start_marker(input: &str, unique_len: usize) -> i64 {
         for ch in buffer.iter().copied() {
             *counts.entry(ch).or_insert(0) += 1;
         }
-        if aoc::histogram(buffer.iter().copied()).len() == unique_len {
+        if buffer.iter().collect::<Counter<_>>().len() == unique_len {
             return (idx as i64) + 1;
         }
     }